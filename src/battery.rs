@@ -0,0 +1,275 @@
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::uptime::Duration;
+use crate::Module;
+
+/// Contains a directory for each power supply, with a type file that is
+/// either Battery, Mains or USB. Energy values are in µWh and µW, charge
+/// values in µAh and µA.
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+#[derive(Debug, PartialEq)]
+struct BatteryInfo {
+    name: String,
+    /// Charge in percent
+    capacity: u32,
+    /// Charging, Discharging, Full or Not charging
+    status: String,
+    /// Full capacity compared to the design capacity in percent
+    health: Option<f32>,
+    /// Time until empty when discharging or until full when charging, in
+    /// seconds
+    time_remaining: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Battery {
+    #[serde(skip)]
+    batteries: Vec<BatteryInfo>,
+    /// None when the system has no AC adapter
+    #[serde(skip)]
+    ac_online: Option<bool>,
+
+    header: String,
+    /// Whether to show the battery status, like Charging
+    show_status: bool,
+    /// Whether to show the battery health
+    show_health: bool,
+    /// Whether to show the estimated time until empty or full
+    show_time_remaining: bool,
+    /// Whether to show if the AC adapter is connected
+    show_ac: bool,
+}
+
+fn read_value(dir: &Path, file: &str) -> Option<u64> {
+    read_to_string(dir.join(file)).ok()?.trim().parse().ok()
+}
+
+/// Reads a battery from its directory, like /sys/class/power_supply/BAT0
+fn read_battery(dir: &Path) -> Option<BatteryInfo> {
+    let name = dir.file_name()?.to_str()?.to_string();
+    let status = read_to_string(dir.join("status"))
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|_| String::from("Unknown"));
+
+    // Some batteries only report energy, others only charge and some only
+    // the capacity in percent
+    let (now, full, design, rate) = match read_value(dir, "energy_now") {
+        Some(now) => (
+            Some(now),
+            read_value(dir, "energy_full"),
+            read_value(dir, "energy_full_design"),
+            read_value(dir, "power_now"),
+        ),
+        None => (
+            read_value(dir, "charge_now"),
+            read_value(dir, "charge_full"),
+            read_value(dir, "charge_full_design"),
+            read_value(dir, "current_now"),
+        ),
+    };
+
+    let capacity = match (read_value(dir, "capacity"), now, full) {
+        (Some(capacity), _, _) => capacity as u32,
+        (None, Some(now), Some(full)) if full > 0 => (now * 100 / full) as u32,
+        _ => return None,
+    };
+
+    let health = match (full, design) {
+        (Some(full), Some(design)) if design > 0 => Some(full as f32 / design as f32 * 100.0),
+        _ => None,
+    };
+
+    let time_remaining = match (now, rate, status.as_str()) {
+        (Some(now), Some(rate), "Discharging") if rate > 0 => Some(now * 3600 / rate),
+        (Some(now), Some(rate), "Charging") if rate > 0 => {
+            full.map(|full| full.saturating_sub(now) * 3600 / rate)
+        }
+        _ => None,
+    };
+
+    Some(BatteryInfo {
+        name,
+        capacity,
+        status,
+        health,
+        time_remaining: time_remaining.map(|t| t as u32),
+    })
+}
+
+/// Reads all batteries and whether an AC adapter is connected from a
+/// directory like /sys/class/power_supply
+fn get_power_supplies(path: &Path) -> (Vec<BatteryInfo>, Option<bool>) {
+    let mut supplies = match read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect::<Vec<_>>(),
+        Err(_) => return (Vec::new(), None),
+    };
+    supplies.sort();
+
+    let mut batteries = Vec::new();
+    let mut ac_online = None;
+    for dir in supplies.iter() {
+        match read_to_string(dir.join("type")).as_deref().map(str::trim) {
+            Ok("Battery") => {
+                // Skips batteries of peripherals like mice
+                if read_to_string(dir.join("scope")).is_ok_and(|s| s.trim() == "Device") {
+                    continue;
+                }
+                if let Some(battery) = read_battery(dir) {
+                    batteries.push(battery);
+                }
+            }
+            Ok("Mains") => {
+                let online = read_value(dir, "online") == Some(1);
+                ac_online = Some(ac_online.unwrap_or(false) || online);
+            }
+            _ => (),
+        }
+    }
+    (batteries, ac_online)
+}
+
+impl Default for Battery {
+    fn default() -> Self {
+        let (batteries, ac_online) = get_power_supplies(Path::new(POWER_SUPPLY_PATH));
+        Self {
+            batteries,
+            ac_online,
+            header: String::from("Battery"),
+            show_status: true,
+            show_health: false,
+            show_time_remaining: true,
+            show_ac: false,
+        }
+    }
+}
+
+impl Module for Battery {
+    fn print(&self, config: &Config) {
+        // Hide the module on systems without a battery
+        if self.batteries.is_empty() {
+            return;
+        }
+
+        let mut batteries = self
+            .batteries
+            .iter()
+            .map(|b| {
+                let mut battery = format!("{}%", b.capacity);
+                if self.batteries.len() > 1 {
+                    battery = format!("{} {}", b.name, battery);
+                }
+
+                let mut details = Vec::new();
+                if config.battery.show_status {
+                    details.push(b.status.clone());
+                }
+                if let (true, Some(time)) = (config.battery.show_time_remaining, b.time_remaining) {
+                    details.push(format!(
+                        "{} left",
+                        Duration::from_secs(time).to_string(true)
+                    ));
+                }
+                if let (true, Some(health)) = (config.battery.show_health, b.health) {
+                    details.push(format!("{:.0}% health", health));
+                }
+                if !details.is_empty() {
+                    battery.push_str(&format!(" ({})", details.join(", ")));
+                }
+                battery
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if let (true, Some(online)) = (config.battery.show_ac, self.ac_online) {
+            let ac = if online {
+                "AC connected"
+            } else {
+                "AC disconnected"
+            };
+            batteries.push_str(&format!(", {}", ac));
+        }
+
+        println!(
+            "{}: {}",
+            config.color.bold().paint(&config.battery.header),
+            batteries
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    #[test]
+    fn test_get_power_supplies() {
+        let dir = fixture_dir("power-supply");
+        write_file(&dir, "AC/type", "Mains\n");
+        write_file(&dir, "AC/online", "0\n");
+        write_file(&dir, "BAT0/type", "Battery\n");
+        write_file(&dir, "BAT0/status", "Discharging\n");
+        write_file(&dir, "BAT0/capacity", "80\n");
+        write_file(&dir, "BAT0/energy_now", "40000000\n");
+        write_file(&dir, "BAT0/energy_full", "50000000\n");
+        write_file(&dir, "BAT0/energy_full_design", "57000000\n");
+        write_file(&dir, "BAT0/power_now", "10000000\n");
+        write_file(&dir, "BAT1/type", "Battery\n");
+        write_file(&dir, "BAT1/status", "Charging\n");
+        write_file(&dir, "BAT1/charge_now", "1000000\n");
+        write_file(&dir, "BAT1/charge_full", "4000000\n");
+        write_file(&dir, "BAT1/current_now", "1500000\n");
+        write_file(&dir, "CMB0/type", "Battery\n");
+        write_file(&dir, "CMB0/status", "Discharging\n");
+        write_file(&dir, "CMB0/capacity", "64\n");
+        write_file(&dir, "hidpp_battery_0/type", "Battery\n");
+        write_file(&dir, "hidpp_battery_0/scope", "Device\n");
+        write_file(&dir, "hidpp_battery_0/capacity", "50\n");
+
+        let (batteries, ac_online) = get_power_supplies(&dir);
+        assert_eq!(ac_online, Some(false));
+        assert_eq!(batteries.len(), 3);
+        assert_eq!(batteries[0].capacity, 80);
+        // 40 Wh at 10 W
+        assert_eq!(batteries[0].time_remaining, Some(4 * 3600));
+        assert_eq!(batteries[0].health.map(|h| h.round()), Some(88.0));
+        assert_eq!(
+            batteries[1],
+            BatteryInfo {
+                name: String::from("BAT1"),
+                capacity: 25,
+                status: String::from("Charging"),
+                health: None,
+                // 3 Ah left to charge at 1.5 A
+                time_remaining: Some(2 * 3600),
+            }
+        );
+        // Without energy or charge values only the capacity is known
+        assert_eq!(
+            batteries[2],
+            BatteryInfo {
+                name: String::from("CMB0"),
+                capacity: 64,
+                status: String::from("Discharging"),
+                health: None,
+                time_remaining: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_no_batteries() {
+        let dir = fixture_dir("power-supply-desktop");
+        let (batteries, ac_online) = get_power_supplies(&dir);
+        assert!(batteries.is_empty());
+        assert_eq!(ac_online, None);
+    }
+}
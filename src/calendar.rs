@@ -0,0 +1,201 @@
+use crate::tzif;
+
+const MONTHS: &[&str] = &[
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const WEEKDAYS: &[&str] = &[
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// Converts days since the Unix epoch to a (year, month, day) date in the
+/// proleptic Gregorian calendar. See
+/// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Converts a date in the proleptic Gregorian calendar to days since the
+/// Unix epoch. See
+/// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+pub fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// A point in time broken down into calendar fields, at a given offset from
+/// UTC
+#[derive(Debug, PartialEq)]
+pub struct CalendarTime {
+    pub year: i64,
+    /// 1 to 12
+    pub month: u32,
+    /// 1 to 31
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// 0 for Sunday to 6 for Saturday
+    pub weekday: u32,
+    /// Offset from UTC in seconds
+    pub offset: i64,
+    /// Abbreviated time zone name, like CEST
+    pub zone: String,
+    /// Seconds since the Unix epoch
+    timestamp: i64,
+}
+
+impl CalendarTime {
+    /// Breaks down a Unix timestamp, shifted by an offset from UTC in seconds
+    pub fn from_timestamp(timestamp: i64, offset: i64, zone: &str) -> Self {
+        let local = timestamp + offset;
+        let days = local.div_euclid(86400);
+        let secs = local.rem_euclid(86400);
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            hour: (secs / 3600) as u32,
+            minute: (secs / 60 % 60) as u32,
+            second: (secs % 60) as u32,
+            weekday: (days + 4).rem_euclid(7) as u32,
+            offset,
+            zone: zone.to_string(),
+            timestamp,
+        }
+    }
+
+    /// Breaks down a Unix timestamp in the local time zone of the system
+    pub fn local(timestamp: i64) -> Self {
+        let (offset, zone) = tzif::local_offset(timestamp);
+        Self::from_timestamp(timestamp, offset, &zone)
+    }
+
+    /// Day of the year, starting from 1
+    fn year_day(&self) -> u32 {
+        let days_before_month = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+        let leap_day = if self.month > 2 && is_leap_year(self.year) {
+            1
+        } else {
+            0
+        };
+        days_before_month[self.month as usize - 1] + leap_day + self.day
+    }
+
+    /// Formats the time with a strftime-like pattern. Supported conversions
+    /// are %Y %y %m %d %e %j %H %I %M %S %p %a %A %b %B %h %Z %z %s %F %T %R
+    /// %D and %%. Other conversions are left as they are.
+    pub fn format(&self, pattern: &str) -> String {
+        let mut result = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+            let hour12 = match self.hour % 12 {
+                0 => 12,
+                h => h,
+            };
+            let month = MONTHS[self.month as usize - 1];
+            let weekday = WEEKDAYS[self.weekday as usize];
+            match chars.next() {
+                Some('Y') => result.push_str(&self.year.to_string()),
+                Some('y') => result.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                Some('m') => result.push_str(&format!("{:02}", self.month)),
+                Some('d') => result.push_str(&format!("{:02}", self.day)),
+                Some('e') => result.push_str(&format!("{:2}", self.day)),
+                Some('j') => result.push_str(&format!("{:03}", self.year_day())),
+                Some('H') => result.push_str(&format!("{:02}", self.hour)),
+                Some('I') => result.push_str(&format!("{:02}", hour12)),
+                Some('M') => result.push_str(&format!("{:02}", self.minute)),
+                Some('S') => result.push_str(&format!("{:02}", self.second)),
+                Some('p') => result.push_str(if self.hour < 12 { "AM" } else { "PM" }),
+                Some('a') => result.push_str(&weekday[..3]),
+                Some('A') => result.push_str(weekday),
+                Some('b') | Some('h') => result.push_str(&month[..3]),
+                Some('B') => result.push_str(month),
+                Some('Z') => result.push_str(&self.zone),
+                Some('z') => {
+                    let sign = if self.offset < 0 { '-' } else { '+' };
+                    let offset = self.offset.abs();
+                    result.push_str(&format!(
+                        "{}{:02}{:02}",
+                        sign,
+                        offset / 3600,
+                        offset / 60 % 60
+                    ));
+                }
+                Some('s') => result.push_str(&self.timestamp.to_string()),
+                Some('F') => result.push_str(&self.format("%Y-%m-%d")),
+                Some('T') => result.push_str(&self.format("%H:%M:%S")),
+                Some('R') => result.push_str(&self.format("%H:%M")),
+                Some('D') => result.push_str(&self.format("%m/%d/%y")),
+                Some('%') => result.push('%'),
+                Some(other) => {
+                    result.push('%');
+                    result.push(other);
+                }
+                None => result.push('%'),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_format() {
+        // 2024-02-29 13:05:09 UTC, a leap day
+        let time = CalendarTime::from_timestamp(1709211909, 3600, "CET");
+        assert_eq!(time.format("%F %T %Z %z"), "2024-02-29 14:05:09 CET +0100");
+        assert_eq!(
+            time.format("%a %e %b %y, %I:%M %p (day %j) 100%%"),
+            "Thu 29 Feb 24, 02:05 PM (day 060) 100%"
+        );
+        assert_eq!(
+            time.format("%A %d %B %Y %q"),
+            "Thursday 29 February 2024 %q"
+        );
+
+        let time = CalendarTime::from_timestamp(-1, -12600, "-0330");
+        assert_eq!(time.format("%F %T %z"), "1969-12-31 20:29:59 -0330");
+    }
+}
@@ -4,11 +4,28 @@ use std::io::ErrorKind::NotFound;
 use ansi_term::Color;
 use serde::{Deserialize, Serialize};
 
+use crate::battery::Battery;
 use crate::cpu::Cpu;
+use crate::cpu_usage::CpuUsage;
+use crate::datetime::DateTime;
+use crate::desktop::{Desktop, WindowManager};
+use crate::display::Display;
+use crate::host::Host;
+use crate::init::Init;
+use crate::load::{Load, Processes};
+use crate::locale::{Keyboard, Locale, Timezone};
 use crate::memory::Memory;
+use crate::network::Network;
+use crate::os::Kernel;
 use crate::packages::Packages;
+use crate::sensors::Sensors;
 use crate::shell::Shell;
+use crate::terminal::Terminal;
+use crate::terminal_font::TerminalFont;
+use crate::theme::{Cursor, GtkTheme, Icons};
 use crate::uptime::Uptime;
+use crate::users::Users;
+use crate::virtualization::Virtualization;
 
 const BINARY_NAME: &str = env!("CARGO_PKG_NAME");
 
@@ -27,7 +44,53 @@ pub struct Config {
 
     pub cpu: Cpu,
 
+    pub cpu_usage: CpuUsage,
+
     pub packages: Packages,
+
+    pub sensors: Sensors,
+
+    pub battery: Battery,
+
+    pub host: Host,
+
+    pub display: Display,
+
+    pub de: Desktop,
+
+    pub wm: WindowManager,
+
+    pub terminal: Terminal,
+
+    pub terminal_font: TerminalFont,
+
+    pub gtk_theme: GtkTheme,
+
+    pub icons: Icons,
+
+    pub cursor: Cursor,
+
+    pub network: Network,
+
+    pub load: Load,
+
+    pub processes: Processes,
+
+    pub users: Users,
+
+    pub init: Init,
+
+    pub virtualization: Virtualization,
+
+    pub kernel: Kernel,
+
+    pub locale: Locale,
+
+    pub timezone: Timezone,
+
+    pub keyboard: Keyboard,
+
+    pub datetime: DateTime,
 }
 
 impl Default for Config {
@@ -38,7 +101,30 @@ impl Default for Config {
             shell: Shell::default(),
             uptime: Uptime::default(),
             cpu: Cpu::default(),
+            cpu_usage: CpuUsage::default(),
             packages: Packages::default(),
+            sensors: Sensors::default(),
+            battery: Battery::default(),
+            host: Host::default(),
+            display: Display::default(),
+            de: Desktop::default(),
+            wm: WindowManager::default(),
+            terminal: Terminal::default(),
+            terminal_font: TerminalFont::default(),
+            gtk_theme: GtkTheme::default(),
+            icons: Icons::default(),
+            cursor: Cursor::default(),
+            network: Network::default(),
+            load: Load::default(),
+            processes: Processes::default(),
+            users: Users::default(),
+            init: Init::default(),
+            virtualization: Virtualization::default(),
+            kernel: Kernel::default(),
+            locale: Locale::default(),
+            timezone: Timezone::default(),
+            keyboard: Keyboard::default(),
+            datetime: DateTime::default(),
         }
     }
 }
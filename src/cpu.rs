@@ -1,5 +1,6 @@
 use std::collections::HashMap;
-use std::fs::read_to_string;
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
@@ -7,6 +8,9 @@ use crate::config::Config;
 use crate::Module;
 
 const CPU_INFO_PATH: &str = "/proc/cpuinfo";
+/// Contains an index* directory for each cache of the first core, with the
+/// files level, type and size
+const CPU_CACHE_PATH: &str = "/sys/devices/system/cpu/cpu0/cache";
 
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
@@ -16,11 +20,54 @@ pub struct Cpu {
     model: String,
     #[serde(skip)]
     cores: u32,
+    #[serde(skip)]
+    caches: Vec<Cache>,
+    /// Instruction set flags from the flags (x86) or Features (ARM) line
+    #[serde(skip)]
+    flags: Vec<String>,
 
     /// Whether to add core count to cpu output
     show_core_count: bool,
     /// Whether to remove extra branding like 'Quad-Core' from the model name
     shorten_model: bool,
+
+    cache_header: String,
+    /// Whether to show the cache sizes on a separate line
+    show_cache: bool,
+
+    flags_header: String,
+    /// Whether to show which of the highlighted flags the cpu supports
+    show_flags: bool,
+    /// Flags to look for, as named in /proc/cpuinfo
+    highlighted_flags: Vec<String>,
+}
+
+struct Cache {
+    level: u32,
+    /// Data, Instruction or Unified
+    cache_type: String,
+    /// Size in KiB
+    size: u32,
+}
+
+impl Cache {
+    /// Returns a name like L1d, L1i or L2
+    fn name(&self) -> String {
+        match self.cache_type.as_str() {
+            "Data" => format!("L{}d", self.level),
+            "Instruction" => format!("L{}i", self.level),
+            _ => format!("L{}", self.level),
+        }
+    }
+
+    /// Returns the size like: 48 KiB or 2 MiB
+    fn size_str(&self) -> String {
+        if self.size >= 1024 && self.size % 1024 == 0 {
+            format!("{} MiB", self.size / 1024)
+        } else {
+            format!("{} KiB", self.size)
+        }
+    }
 }
 
 /// Parses contents of /proc/cpuinfo into a HashMap.
@@ -32,15 +79,15 @@ fn parse(contents: &str) -> HashMap<&str, &str> {
         .map(|line| {
             let split = line.split(':').collect::<Vec<_>>();
             // Safe to unwrap, since data is almost guaranteed to be in a valid format.
-            let key = split.get(0).unwrap().trim();
+            let key = split.first().unwrap().trim();
             let val = split.get(1).unwrap_or(&"").trim();
             (key, val)
         })
         .collect()
 }
 
-/// Gets cpu model name and core count and returns result as tuple
-fn get_cpu() -> (String, u32) {
+/// Gets cpu model name, core count and flags and returns result as tuple
+fn get_cpu() -> (String, u32, Vec<String>) {
     let contents = read_to_string(CPU_INFO_PATH).expect("Failed to read /proc/cpuinfo");
     let cpu_info = parse(&contents);
     let model = cpu_info
@@ -54,7 +101,46 @@ fn get_cpu() -> (String, u32) {
         .parse::<u32>()
         .unwrap();
 
-    (model, cores)
+    let flags = cpu_info
+        .get("flags")
+        .or_else(|| cpu_info.get("Features"))
+        .map(|f| f.split_whitespace().map(String::from).collect())
+        .unwrap_or_default();
+
+    (model, cores, flags)
+}
+
+/// Parses a cache size like "32K" into KiB
+fn parse_cache_size(size: &str) -> Option<u32> {
+    let size = size.trim();
+    if let Some(mib) = size.strip_suffix('M') {
+        return mib.parse::<u32>().ok().map(|s| s * 1024);
+    }
+    size.trim_end_matches('K').parse::<u32>().ok()
+}
+
+/// Reads the caches from a directory like /sys/devices/system/cpu/cpu0/cache
+fn get_caches(path: &Path) -> Vec<Cache> {
+    let entries = match read_dir(path) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+    let mut caches = entries
+        .filter_map(|entry| {
+            let dir = entry.ok()?.path();
+            if !dir.file_name()?.to_str()?.starts_with("index") {
+                return None;
+            }
+            let read = |file: &str| read_to_string(dir.join(file)).ok();
+            Some(Cache {
+                level: read("level")?.trim().parse().ok()?,
+                cache_type: read("type")?.trim().to_string(),
+                size: parse_cache_size(&read("size")?)?,
+            })
+        })
+        .collect::<Vec<_>>();
+    caches.sort_by_key(|c| (c.level, c.name()));
+    caches
 }
 
 /// Removes some extra branding from the cpu model
@@ -75,6 +161,13 @@ fn shorten_model_name(model: String) -> String {
     patterns.iter().fold(model, |m, p| m.replace(p, ""))
 }
 
+impl Cpu {
+    /// Returns the number of cpu cores
+    pub fn cores(&self) -> u32 {
+        self.cores
+    }
+}
+
 impl Default for Cpu {
     fn default() -> Self {
         let cpu = get_cpu();
@@ -82,8 +175,18 @@ impl Default for Cpu {
             header: String::from("CPU"),
             model: cpu.0,
             cores: cpu.1,
+            caches: get_caches(Path::new(CPU_CACHE_PATH)),
+            flags: cpu.2,
             show_core_count: true,
             shorten_model: true,
+            cache_header: String::from("CPU Cache"),
+            show_cache: false,
+            flags_header: String::from("CPU Flags"),
+            show_flags: false,
+            highlighted_flags: ["avx2", "avx512f", "sve", "aes", "sha_ni", "sha2"]
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
         }
     }
 }
@@ -101,5 +204,71 @@ impl Module for Cpu {
         }
 
         println!("{}: {}", config.color.bold().paint(&config.cpu.header), cpu);
+
+        if config.cpu.show_cache && !self.caches.is_empty() {
+            let caches = self
+                .caches
+                .iter()
+                .map(|c| format!("{} {}", c.name(), c.size_str()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.cpu.cache_header),
+                caches
+            );
+        }
+
+        if config.cpu.show_flags {
+            let flags = config
+                .cpu
+                .highlighted_flags
+                .iter()
+                .filter(|f| self.flags.contains(f))
+                .map(|f| f.to_uppercase())
+                .collect::<Vec<_>>();
+            if !flags.is_empty() {
+                println!(
+                    "{}: {}",
+                    config.color.bold().paint(&config.cpu.flags_header),
+                    flags.join(" ")
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    #[test]
+    fn test_get_caches() {
+        let dir = fixture_dir("cpu-cache");
+        for (index, level, cache_type, size) in &[
+            ("index0", "1", "Data", "48K"),
+            ("index1", "1", "Instruction", "32K"),
+            ("index2", "2", "Unified", "2048K"),
+            ("index3", "3", "Unified", "107520K"),
+        ] {
+            write_file(&dir, &format!("{}/level", index), format!("{}\n", level));
+            write_file(
+                &dir,
+                &format!("{}/type", index),
+                format!("{}\n", cache_type),
+            );
+            write_file(&dir, &format!("{}/size", index), format!("{}\n", size));
+        }
+        write_file(&dir, "uevent", "");
+
+        let caches = get_caches(&dir)
+            .iter()
+            .map(|c| format!("{} {}", c.name(), c.size_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            caches,
+            vec!["L1d 48 KiB", "L1i 32 KiB", "L2 2 MiB", "L3 105 MiB"]
+        );
     }
 }
@@ -0,0 +1,254 @@
+use std::fs::read_to_string;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::Module;
+
+/// The lines starting with "cpu" in /proc/stat contain the amount of time
+/// (in USER_HZ) the cpus have spent in different states since boot. The first
+/// line is the sum of all cores, followed by one line per core.
+const STAT_PATH: &str = "/proc/stat";
+
+/// Characters used to draw a sparkline, from lowest to highest usage
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// First sample of /proc/stat, taken at process start so that loading the
+/// configuration and the modules counts towards the sampling interval
+static FIRST_SAMPLE: OnceLock<Option<Sample>> = OnceLock::new();
+
+/// How the usage of each core is shown
+#[derive(Serialize, Deserialize)]
+enum PerCoreStyle {
+    Hidden,
+    Percentage,
+    Sparkline,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct CpuUsage {
+    header: String,
+    /// Whether to show the module. Waiting for the interval to pass can
+    /// make the output slower, so it is off by default.
+    enabled: bool,
+    /// Minimum time in milliseconds between the two samples of /proc/stat
+    interval: u64,
+    /// Whether to draw a usage bar in front of the percentage
+    show_bar: bool,
+    /// Width of the usage bar in characters
+    bar_width: usize,
+    /// How to show the usage of each core. Possible values include Hidden,
+    /// Percentage and Sparkline
+    per_core: PerCoreStyle,
+}
+
+/// Time spent by a cpu (or all cpus) since boot
+#[derive(Debug, PartialEq)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+impl CpuTimes {
+    /// Returns the usage between an earlier sample and this one as a
+    /// percentage
+    fn usage_since(&self, earlier: &CpuTimes) -> f32 {
+        let total = self.total.saturating_sub(earlier.total);
+        let idle = self.idle.saturating_sub(earlier.idle);
+        if total == 0 {
+            return 0.0;
+        }
+        (total - idle.min(total)) as f32 / total as f32 * 100.0
+    }
+}
+
+struct Sample {
+    taken: Instant,
+    /// Sum of all cores
+    all: CpuTimes,
+    cores: Vec<CpuTimes>,
+}
+
+/// Parses a cpu line from /proc/stat. Line is in the form:
+/// cpuN user nice system idle iowait irq softirq steal guest guest_nice
+fn parse_cpu_line(line: &str) -> Option<CpuTimes> {
+    let values = line
+        .split_whitespace()
+        .skip(1)
+        .map(|v| v.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    if values.len() < 4 {
+        return None;
+    }
+    // idle + iowait
+    let idle = values[3] + values.get(4).unwrap_or(&0);
+    // guest and guest_nice are already included in user and nice
+    let total = values.iter().take(8).sum();
+    Some(CpuTimes { idle, total })
+}
+
+/// Parses the contents of /proc/stat into the times of all cores combined
+/// and the times of each core
+fn parse(contents: &str) -> Option<(CpuTimes, Vec<CpuTimes>)> {
+    let mut lines = contents.lines().filter(|line| line.starts_with("cpu"));
+    let all = parse_cpu_line(lines.next()?)?;
+    let cores = lines.filter_map(parse_cpu_line).collect();
+    Some((all, cores))
+}
+
+/// Takes the first sample of /proc/stat unless it was already taken. The
+/// second one is taken when printing.
+pub fn take_first_sample() {
+    FIRST_SAMPLE.get_or_init(get_sample);
+}
+
+fn get_sample() -> Option<Sample> {
+    let contents = match read_to_string(STAT_PATH) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Failed to read {}, {}", STAT_PATH, e);
+            return None;
+        }
+    };
+    let (all, cores) = parse(&contents)?;
+    Some(Sample {
+        taken: Instant::now(),
+        all,
+        cores,
+    })
+}
+
+/// Draws a bar like: [####      ]
+fn usage_bar(percent: f32, width: usize) -> String {
+    let filled = ((percent / 100.0 * width as f32).round() as usize).min(width);
+    format!("[{}{}]", "#".repeat(filled), " ".repeat(width - filled))
+}
+
+/// Draws one sparkline character per value
+fn sparkline(percents: &[f32]) -> String {
+    percents
+        .iter()
+        .map(|p| {
+            let index = (p / 100.0 * SPARKS.len() as f32) as usize;
+            SPARKS[index.min(SPARKS.len() - 1)]
+        })
+        .collect()
+}
+
+impl Default for CpuUsage {
+    fn default() -> Self {
+        Self {
+            header: String::from("CPU Usage"),
+            enabled: false,
+            interval: 200,
+            show_bar: false,
+            bar_width: 10,
+            per_core: PerCoreStyle::Hidden,
+        }
+    }
+}
+
+impl Module for CpuUsage {
+    fn print(&self, config: &Config) {
+        if !config.cpu_usage.enabled {
+            return;
+        }
+        let first = match FIRST_SAMPLE.get_or_init(get_sample) {
+            Some(s) => s,
+            None => return,
+        };
+        let interval = Duration::from_millis(config.cpu_usage.interval);
+        thread::sleep(interval.saturating_sub(first.taken.elapsed()));
+        let second = match get_sample() {
+            Some(s) => s,
+            None => return,
+        };
+
+        let percent = second.all.usage_since(&first.all);
+        let mut usage = format!("{:.0}%", percent);
+        if config.cpu_usage.show_bar {
+            usage = format!(
+                "{} {}",
+                usage_bar(percent, config.cpu_usage.bar_width),
+                usage
+            );
+        }
+
+        let per_core = second
+            .cores
+            .iter()
+            .zip(first.cores.iter())
+            .map(|(now, earlier)| now.usage_since(earlier))
+            .collect::<Vec<_>>();
+        match config.cpu_usage.per_core {
+            PerCoreStyle::Hidden => (),
+            PerCoreStyle::Percentage => {
+                let cores = per_core
+                    .iter()
+                    .map(|p| format!("{:.0}%", p))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                usage.push_str(&format!(" ({})", cores));
+            }
+            PerCoreStyle::Sparkline => usage.push_str(&format!(" {}", sparkline(&per_core))),
+        }
+
+        println!(
+            "{}: {}",
+            config.color.bold().paint(&config.cpu_usage.header),
+            usage
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_parse() {
+        let input = "cpu  10132153 290696 3084719 46828483 16683 0 25195 0 175628 0
+cpu0 1393280 32966 572056 13343292 6130 0 17875 0 23933 0
+cpu1 1335676 31904 477468 11093524 2700 0 3187 0 35960 0
+intr 1462898 0 0 0 0 0 0 0 0 0
+ctxt 2387016
+btime 1620000000
+processes 42553
+";
+        let (all, cores) = parse(input).unwrap();
+        assert_eq!(
+            all,
+            CpuTimes {
+                idle: 46828483 + 16683,
+                total: 10132153 + 290696 + 3084719 + 46828483 + 16683 + 25195
+            }
+        );
+        assert_eq!(cores.len(), 2);
+    }
+
+    #[test]
+    fn test_usage_since() {
+        let earlier = CpuTimes {
+            idle: 100,
+            total: 200,
+        };
+        let now = CpuTimes {
+            idle: 175,
+            total: 300,
+        };
+        assert_eq!(now.usage_since(&earlier), 25.0);
+        // No time passed
+        assert_eq!(earlier.usage_since(&earlier), 0.0);
+    }
+
+    #[test]
+    fn test_rendering() {
+        assert_eq!(usage_bar(50.0, 10), String::from("[#####     ]"));
+        assert_eq!(usage_bar(100.0, 4), String::from("[####]"));
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0]), String::from("▁▅█"));
+    }
+}
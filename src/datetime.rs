@@ -0,0 +1,81 @@
+use std::env;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::CalendarTime;
+use crate::config::Config;
+use crate::tzif;
+use crate::Module;
+
+/// Formats a timestamp in a time zone. Falls back to UTC when the time zone
+/// can't be loaded.
+fn format_time(root: &Path, timestamp: i64, timezone: Option<&str>, format: &str) -> String {
+    let (offset, zone) = tzif::zone_offset(root, timezone, timestamp);
+    CalendarTime::from_timestamp(timestamp, offset, &zone).format(format)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct DateTime {
+    header: String,
+    /// strftime-like pattern, see calendar.rs for the supported conversions
+    format: String,
+    /// Time zone like Europe/Berlin or a POSIX TZ string like EST5EDT. When
+    /// empty, TZ or the system time zone is used.
+    timezone: String,
+}
+
+impl Default for DateTime {
+    fn default() -> Self {
+        Self {
+            header: String::from("Date"),
+            format: String::from("%a %d %b %Y %H:%M %Z"),
+            timezone: String::new(),
+        }
+    }
+}
+
+impl Module for DateTime {
+    fn print(&self, config: &Config) {
+        let timezone = match config.datetime.timezone.as_str() {
+            "" => env::var("TZ").ok(),
+            timezone => Some(timezone.to_string()),
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        println!(
+            "{}: {}",
+            config.color.bold().paint(&config.datetime.header),
+            format_time(
+                Path::new("/"),
+                now,
+                timezone.as_deref(),
+                &config.datetime.format
+            )
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::fixture_dir;
+
+    #[test]
+    fn test_format_time() {
+        let root = fixture_dir("datetime");
+        // 2024-07-01 12:00:00 UTC
+        assert_eq!(
+            format_time(&root, 1719835200, Some("EST5EDT"), "%F %H:%M %Z"),
+            "2024-07-01 08:00 EDT"
+        );
+        // Without /etc/localtime the time is shown in UTC
+        assert_eq!(
+            format_time(&root, 1719835200, None, "%F %H:%M %Z"),
+            "2024-07-01 12:00 UTC"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use std::env;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::process::{process_names, PROC_PATH};
+use crate::Module;
+
+/// Process names of known window managers and compositors, with the name to
+/// show for them
+const WINDOW_MANAGERS: &[(&str, &str)] = &[
+    ("sway", "Sway"),
+    ("Hyprland", "Hyprland"),
+    ("i3", "i3"),
+    ("bspwm", "bspwm"),
+    ("kwin_wayland", "KWin"),
+    ("kwin_x11", "KWin"),
+    ("kwin", "KWin"),
+    ("gnome-shell", "Mutter"),
+    ("mutter", "Mutter"),
+    ("cinnamon", "Muffin"),
+    ("muffin", "Muffin"),
+    ("marco", "Marco"),
+    ("xfwm4", "Xfwm4"),
+    ("openbox", "Openbox"),
+    ("awesome", "awesome"),
+    ("dwm", "dwm"),
+    ("qtile", "Qtile"),
+    ("xmonad", "xmonad"),
+    ("herbstluftwm", "herbstluftwm"),
+    ("river", "river"),
+    ("niri", "niri"),
+    ("wayfire", "Wayfire"),
+    ("labwc", "labwc"),
+    ("weston", "Weston"),
+    ("spectrwm", "spectrwm"),
+    ("leftwm", "LeftWM"),
+    ("fluxbox", "Fluxbox"),
+    ("icewm", "IceWM"),
+    ("fvwm", "FVWM"),
+    ("enlightenment", "Enlightenment"),
+];
+
+/// Known desktops by their name in XDG_CURRENT_DESKTOP, which can differ
+/// from the usual name
+const DESKTOP_NAMES: &[(&str, &str)] = &[
+    ("GNOME", "GNOME"),
+    ("KDE", "Plasma"),
+    ("X-Cinnamon", "Cinnamon"),
+    ("XFCE", "Xfce"),
+    ("MATE", "MATE"),
+    ("Budgie", "Budgie"),
+    ("LXQt", "LXQt"),
+    ("LXDE", "LXDE"),
+    ("Unity", "Unity"),
+    ("Pantheon", "Pantheon"),
+    ("Deepin", "Deepin"),
+    ("COSMIC", "COSMIC"),
+    ("Enlightenment", "Enlightenment"),
+];
+
+/// Files containing the version of a desktop environment
+const GNOME_VERSION_PATH: &str = "/usr/share/gnome/gnome-version.xml";
+const MATE_VERSION_PATH: &str = "/usr/share/mate-about/mate-version.xml";
+const PLASMA_SESSION_PATHS: &[&str] = &[
+    "/usr/share/wayland-sessions/plasma.desktop",
+    "/usr/share/xsessions/plasma.desktop",
+];
+
+/// Returns the desktop environment from the values of XDG_CURRENT_DESKTOP
+/// and DESKTOP_SESSION
+fn parse_desktop(current_desktop: Option<&str>, desktop_session: Option<&str>) -> Option<String> {
+    let desktops = match current_desktop.filter(|d| !d.is_empty()) {
+        // Can be a list like "Budgie:GNOME", ordered from the most specific
+        // desktop to the ones it is compatible with. Distributions prepend
+        // their own name, like "ubuntu:GNOME".
+        Some(desktops) => desktops.split(':').collect::<Vec<_>>(),
+        // Can be a path like /usr/share/xsessions/plasma
+        None => vec![desktop_session?.rsplit('/').next()?],
+    };
+    let known = desktops.iter().find_map(|desktop| {
+        DESKTOP_NAMES
+            .iter()
+            .find(|(raw, _)| raw.eq_ignore_ascii_case(desktop))
+            .map(|(_, name)| name.to_string())
+    });
+    known.or_else(|| {
+        let desktop = desktops.first()?;
+        (!desktop.is_empty()).then(|| desktop.to_string())
+    })
+}
+
+/// Returns the session type (X11 or Wayland) from the value of
+/// XDG_SESSION_TYPE, falling back to which display variables are set
+fn parse_session_type(
+    session_type: Option<&str>,
+    wayland_display: bool,
+    display: bool,
+) -> Option<String> {
+    match session_type.map(|s| s.to_lowercase()).as_deref() {
+        Some("wayland") => Some(String::from("Wayland")),
+        Some("x11") => Some(String::from("X11")),
+        _ if wayland_display => Some(String::from("Wayland")),
+        _ if display => Some(String::from("X11")),
+        _ => None,
+    }
+}
+
+/// Returns the first known window manager among the process names
+fn find_window_manager(processes: &[String]) -> Option<String> {
+    WINDOW_MANAGERS
+        .iter()
+        .find(|(process, _)| {
+            processes
+                .iter()
+                .any(|p| p == process || (*process == "xmonad" && p.starts_with("xmonad")))
+        })
+        .map(|(_, name)| name.to_string())
+}
+
+/// Returns the contents of an XML tag like <minor>2</minor>
+fn xml_tag<'a>(contents: &'a str, tag: &str) -> Option<&'a str> {
+    let start = contents.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + contents[start..].find(&format!("</{}>", tag))?;
+    Some(contents[start..end].trim())
+}
+
+/// Parses the version from a gnome-version.xml or mate-version.xml file
+fn parse_version_xml(contents: &str) -> Option<String> {
+    let version = ["platform", "minor", "micro"]
+        .iter()
+        .filter_map(|tag| xml_tag(contents, tag))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    if version.is_empty() {
+        return None;
+    }
+    Some(version.join("."))
+}
+
+/// Returns the version of a desktop environment from its version files
+fn get_desktop_version(desktop: &str) -> Option<String> {
+    match desktop {
+        "GNOME" => parse_version_xml(&read_to_string(GNOME_VERSION_PATH).ok()?),
+        "MATE" => parse_version_xml(&read_to_string(MATE_VERSION_PATH).ok()?),
+        "Plasma" => PLASMA_SESSION_PATHS.iter().find_map(|path| {
+            read_to_string(path).ok()?.lines().find_map(|line| {
+                let version = line.strip_prefix("X-KDE-PluginInfo-Version=")?;
+                Some(version.trim().to_string())
+            })
+        }),
+        _ => None,
+    }
+}
+
+fn get_session_type() -> Option<String> {
+    parse_session_type(
+        env::var("XDG_SESSION_TYPE").ok().as_deref(),
+        env::var_os("WAYLAND_DISPLAY").is_some(),
+        env::var_os("DISPLAY").is_some(),
+    )
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Desktop {
+    #[serde(skip)]
+    desktop: Option<String>,
+
+    header: String,
+    /// Whether to show the version of the desktop environment
+    show_version: bool,
+}
+
+impl Default for Desktop {
+    fn default() -> Self {
+        Self {
+            desktop: parse_desktop(
+                env::var("XDG_CURRENT_DESKTOP").ok().as_deref(),
+                env::var("DESKTOP_SESSION").ok().as_deref(),
+            ),
+            header: String::from("DE"),
+            show_version: false,
+        }
+    }
+}
+
+impl Module for Desktop {
+    fn print(&self, config: &Config) {
+        if let Some(desktop) = &self.desktop {
+            let mut de = desktop.clone();
+            if config.de.show_version {
+                if let Some(version) = get_desktop_version(desktop) {
+                    de.push_str(&format!(" {}", version));
+                }
+            }
+            println!("{}: {}", config.color.bold().paint(&config.de.header), de);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowManager {
+    #[serde(skip)]
+    window_manager: Option<String>,
+    /// X11 or Wayland
+    #[serde(skip)]
+    session_type: Option<String>,
+
+    header: String,
+    /// Whether to show if the session is running on X11 or Wayland
+    show_session_type: bool,
+}
+
+impl Default for WindowManager {
+    fn default() -> Self {
+        Self {
+            window_manager: find_window_manager(&process_names(Path::new(PROC_PATH))),
+            session_type: get_session_type(),
+            header: String::from("WM"),
+            show_session_type: true,
+        }
+    }
+}
+
+impl Module for WindowManager {
+    fn print(&self, config: &Config) {
+        if let Some(window_manager) = &self.window_manager {
+            let mut wm = window_manager.clone();
+            if let (true, Some(session_type)) = (config.wm.show_session_type, &self.session_type) {
+                wm.push_str(&format!(" ({})", session_type));
+            }
+            println!("{}: {}", config.color.bold().paint(&config.wm.header), wm);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    #[test]
+    fn test_parse_desktop() {
+        assert_eq!(
+            parse_desktop(Some("ubuntu:GNOME"), None),
+            Some(String::from("GNOME"))
+        );
+        assert_eq!(
+            parse_desktop(Some("Unity:Unity7:ubuntu"), None),
+            Some(String::from("Unity"))
+        );
+        // Unknown desktops are shown as they are
+        assert_eq!(
+            parse_desktop(Some("Hyprland"), None),
+            Some(String::from("Hyprland"))
+        );
+        assert_eq!(
+            parse_desktop(Some("Budgie:GNOME"), None),
+            Some(String::from("Budgie"))
+        );
+        assert_eq!(
+            parse_desktop(Some("X-Cinnamon"), None),
+            Some(String::from("Cinnamon"))
+        );
+        assert_eq!(
+            parse_desktop(Some("KDE"), None),
+            Some(String::from("Plasma"))
+        );
+        assert_eq!(
+            parse_desktop(None, Some("/usr/share/xsessions/xfce")),
+            Some(String::from("Xfce"))
+        );
+        assert_eq!(parse_desktop(Some(""), None), None);
+        assert_eq!(
+            parse_session_type(Some("wayland"), false, true),
+            Some(String::from("Wayland"))
+        );
+        assert_eq!(
+            parse_session_type(Some("tty"), false, true),
+            Some(String::from("X11"))
+        );
+        assert_eq!(parse_session_type(None, false, false), None);
+    }
+
+    #[test]
+    fn test_find_window_manager() {
+        let dir = fixture_dir("proc-wm");
+        write_file(&dir, "1/comm", "systemd\n");
+        write_file(&dir, "812/comm", "Hyprland\n");
+        write_file(&dir, "901/comm", "waybar\n");
+        write_file(&dir, "self/comm", "yan\n");
+
+        let processes = process_names(&dir);
+        assert_eq!(processes.len(), 3);
+        assert_eq!(
+            find_window_manager(&processes),
+            Some(String::from("Hyprland"))
+        );
+        assert_eq!(
+            find_window_manager(&[String::from("xmonad-x86_64-linux")]),
+            Some(String::from("xmonad"))
+        );
+        assert_eq!(find_window_manager(&[String::from("bash")]), None);
+    }
+
+    #[test]
+    fn test_parse_version_xml() {
+        let input = "<?xml version=\"1.0\"?>
+<gnome-version>
+  <platform>3</platform>
+  <minor>38</minor>
+  <micro>1</micro>
+  <distributor>Arch Linux</distributor>
+  <date>2020-10-05</date>
+</gnome-version>
+";
+        assert_eq!(parse_version_xml(input), Some(String::from("3.38.1")));
+        let input =
+            "<gnome-version><platform>45</platform><minor>2</minor><micro></micro></gnome-version>";
+        assert_eq!(parse_version_xml(input), Some(String::from("45.2")));
+    }
+}
@@ -0,0 +1,364 @@
+use std::fs::{read, read_dir, read_to_string};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::Module;
+
+/// Contains a directory for each connector of each graphics card, like
+/// card0-HDMI-A-1, with the files status, modes and edid
+const DRM_PATH: &str = "/sys/class/drm";
+
+/// Every EDID starts with this header
+const EDID_HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+/// Size of the EDID base block
+const EDID_LENGTH: usize = 128;
+/// Offsets of the four 18 byte descriptors in the base block
+const DESCRIPTOR_OFFSETS: [usize; 4] = [54, 72, 90, 108];
+/// Descriptor tag of the monitor name
+const MONITOR_NAME_TAG: u8 = 0xFC;
+
+/// Names of common monitor manufacturers by their PNP ID
+const MANUFACTURERS: &[(&str, &str)] = &[
+    ("ACR", "Acer"),
+    ("AOC", "AOC"),
+    ("APP", "Apple"),
+    ("AUO", "AU Optronics"),
+    ("AUS", "ASUS"),
+    ("BNQ", "BenQ"),
+    ("BOE", "BOE"),
+    ("CMN", "Chimei Innolux"),
+    ("DEL", "Dell"),
+    ("ENC", "EIZO"),
+    ("GSM", "LG"),
+    ("HWP", "HP"),
+    ("IVM", "Iiyama"),
+    ("LEN", "Lenovo"),
+    ("LGD", "LG Display"),
+    ("MSI", "MSI"),
+    ("NEC", "NEC"),
+    ("PHL", "Philips"),
+    ("SAM", "Samsung"),
+    ("SDC", "Samsung Display"),
+    ("SHP", "Sharp"),
+    ("SNY", "Sony"),
+    ("VSC", "ViewSonic"),
+];
+
+/// Information parsed from the EDID of a monitor
+#[derive(Debug, PartialEq)]
+struct Edid {
+    /// Three letter PNP ID, like DEL
+    manufacturer: String,
+    name: Option<String>,
+    /// Physical size in millimeters
+    size: Option<(u32, u32)>,
+    /// Resolution of the preferred timing
+    resolution: Option<(u32, u32)>,
+    /// Refresh rate of the preferred timing in Hz
+    refresh_rate: Option<f32>,
+}
+
+impl Edid {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < EDID_LENGTH || data[..8] != EDID_HEADER {
+            return None;
+        }
+        let checksum = data[..EDID_LENGTH]
+            .iter()
+            .fold(0u8, |sum, b| sum.wrapping_add(*b));
+        if checksum != 0 {
+            return None;
+        }
+
+        // Three letters of five bits each, where 1 is A
+        let id = u16::from_be_bytes([data[8], data[9]]);
+        let manufacturer = [10, 5, 0]
+            .iter()
+            .map(|shift| (b'A' - 1 + ((id >> shift) & 0x1F) as u8) as char)
+            .collect();
+
+        let mut edid = Self {
+            manufacturer,
+            name: None,
+            size: None,
+            resolution: None,
+            refresh_rate: None,
+        };
+        // Fall back to the size in centimeters
+        if data[21] > 0 && data[22] > 0 {
+            edid.size = Some((data[21] as u32 * 10, data[22] as u32 * 10));
+        }
+
+        for offset in DESCRIPTOR_OFFSETS.iter() {
+            let d = &data[*offset..*offset + 18];
+            let pixel_clock = u16::from_le_bytes([d[0], d[1]]) as u32 * 10_000;
+            if pixel_clock > 0 {
+                // The first detailed timing is the preferred one
+                if edid.resolution.is_some() {
+                    continue;
+                }
+                let h_active = d[2] as u32 | (d[4] as u32 & 0xF0) << 4;
+                let h_blank = d[3] as u32 | (d[4] as u32 & 0x0F) << 8;
+                let v_active = d[5] as u32 | (d[7] as u32 & 0xF0) << 4;
+                let v_blank = d[6] as u32 | (d[7] as u32 & 0x0F) << 8;
+                let h_size = d[12] as u32 | (d[14] as u32 & 0xF0) << 4;
+                let v_size = d[13] as u32 | (d[14] as u32 & 0x0F) << 8;
+
+                edid.resolution = Some((h_active, v_active));
+                let total = (h_active + h_blank) * (v_active + v_blank);
+                if total > 0 {
+                    edid.refresh_rate = Some(pixel_clock as f32 / total as f32);
+                }
+                if h_size > 0 && v_size > 0 {
+                    edid.size = Some((h_size, v_size));
+                }
+            } else if d[3] == MONITOR_NAME_TAG {
+                let name = String::from_utf8_lossy(&d[5..])
+                    .split('\n')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .to_string();
+                if !name.is_empty() {
+                    edid.name = Some(name);
+                }
+            }
+        }
+        Some(edid)
+    }
+
+    /// Returns the monitor model like "Dell U2720Q"
+    fn model(&self) -> String {
+        let manufacturer = MANUFACTURERS
+            .iter()
+            .find(|(id, _)| *id == self.manufacturer)
+            .map_or(self.manufacturer.as_str(), |(_, name)| name);
+        let name = match &self.name {
+            Some(name) => name,
+            None => return manufacturer.to_string(),
+        };
+        // Names often already contain the manufacturer, like "DELL U2720Q"
+        if name
+            .to_lowercase()
+            .starts_with(&manufacturer.to_lowercase())
+        {
+            name.clone()
+        } else {
+            format!("{} {}", manufacturer, name)
+        }
+    }
+
+    /// Returns the diagonal in inches
+    fn diagonal(&self) -> Option<f32> {
+        let (width, height) = self.size?;
+        Some(((width * width + height * height) as f32).sqrt() / 25.4)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Monitor {
+    /// Name of the connector, like HDMI-A-1
+    connector: String,
+    /// Preferred (native) resolution of the monitor
+    resolution: Option<(u32, u32)>,
+    edid: Option<Edid>,
+}
+
+impl Monitor {
+    /// Returns the refresh rate of the EDID preferred timing, when it belongs
+    /// to the shown resolution
+    fn refresh_rate(&self) -> Option<f32> {
+        let edid = self.edid.as_ref()?;
+        if edid.resolution != self.resolution {
+            return None;
+        }
+        edid.refresh_rate
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Display {
+    #[serde(skip)]
+    monitors: Vec<Monitor>,
+
+    header: String,
+    /// Whether to show the monitor manufacturer and model
+    show_model: bool,
+    /// Whether to show the refresh rate
+    show_refresh_rate: bool,
+    /// Whether to show the diagonal size in inches
+    show_size: bool,
+    /// Whether to show the connector, like HDMI-A-1
+    show_connector: bool,
+}
+
+/// Reads the connected monitors from a directory like /sys/class/drm
+fn get_monitors(path: &Path) -> Vec<Monitor> {
+    let mut connectors = match read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect::<Vec<_>>(),
+        Err(_) => return Vec::new(),
+    };
+    connectors.sort();
+
+    connectors
+        .iter()
+        .filter_map(|dir| {
+            // Connectors are named like card0-HDMI-A-1
+            let name = dir.file_name()?.to_str()?;
+            let (card, connector) = name.split_once('-')?;
+            if !card.starts_with("card") {
+                return None;
+            }
+            if read_to_string(dir.join("status")).ok()?.trim() != "connected" {
+                return None;
+            }
+
+            // The modes are the ones supported by the monitor, with the
+            // preferred one first. The active mode isn't exposed in sysfs.
+            let resolution = read_to_string(dir.join("modes")).ok().and_then(|modes| {
+                let (width, height) = modes.lines().next()?.split_once('x')?;
+                let height = height.trim_end_matches(|c: char| !c.is_ascii_digit());
+                Some((width.parse().ok()?, height.parse().ok()?))
+            });
+            let edid = read(dir.join("edid"))
+                .ok()
+                .and_then(|data| Edid::parse(&data));
+
+            Some(Monitor {
+                connector: connector.to_string(),
+                resolution: resolution.or_else(|| edid.as_ref()?.resolution),
+                edid,
+            })
+        })
+        .collect()
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Self {
+            monitors: get_monitors(Path::new(DRM_PATH)),
+            header: String::from("Display"),
+            show_model: true,
+            show_refresh_rate: true,
+            show_size: false,
+            show_connector: false,
+        }
+    }
+}
+
+impl Module for Display {
+    fn print(&self, config: &Config) {
+        for monitor in self.monitors.iter() {
+            let mut display = match monitor.resolution {
+                Some((width, height)) => format!("{}x{}", width, height),
+                None => String::from("Unknown resolution"),
+            };
+
+            if let (true, Some(rate)) = (config.display.show_refresh_rate, monitor.refresh_rate()) {
+                display.push_str(&format!(" @ {:.0}Hz", rate));
+            }
+            if let Some(edid) = &monitor.edid {
+                let mut details = Vec::new();
+                if config.display.show_model {
+                    details.push(edid.model());
+                }
+                if let (true, Some(diagonal)) = (config.display.show_size, edid.diagonal()) {
+                    details.push(format!("{:.0}\"", diagonal));
+                }
+                if !details.is_empty() {
+                    display.push_str(&format!(", {}", details.join(" ")));
+                }
+            }
+
+            if config.display.show_connector {
+                display.push_str(&format!(" ({})", monitor.connector));
+            }
+
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.display.header),
+                display
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    /// Builds the EDID of a 1920x1080 60Hz Dell monitor
+    fn edid_fixture() -> Vec<u8> {
+        let mut edid = vec![0u8; EDID_LENGTH];
+        edid[..8].copy_from_slice(&EDID_HEADER);
+        // "DEL"
+        edid[8] = 0x10;
+        edid[9] = 0xAC;
+        // 53 x 30 cm
+        edid[21] = 53;
+        edid[22] = 30;
+
+        // 148.5 MHz, 1920 + 280 by 1080 + 45, 527 x 296 mm
+        edid[54..72].copy_from_slice(&[
+            0x02, 0x3A, 0x80, 0x18, 0x71, 0x38, 0x2D, 0x40, 0x58, 0x2C, 0x45, 0x00, 0x0F, 0x28,
+            0x21, 0x00, 0x00, 0x1E,
+        ]);
+        edid[72..90].copy_from_slice(&[
+            0x00, 0x00, 0x00, 0xFC, 0x00, b'D', b'E', b'L', b'L', b' ', b'U', b'2', b'4', b'1',
+            b'9', b'H', 0x0A, 0x20,
+        ]);
+
+        let sum = edid.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        edid[127] = 0u8.wrapping_sub(sum);
+        edid
+    }
+
+    #[test]
+    fn test_parse_edid() {
+        let edid = Edid::parse(&edid_fixture()).unwrap();
+        assert_eq!(edid.manufacturer, "DEL");
+        assert_eq!(edid.name, Some(String::from("DELL U2419H")));
+        assert_eq!(edid.size, Some((527, 296)));
+        assert_eq!(edid.resolution, Some((1920, 1080)));
+        assert_eq!(edid.refresh_rate, Some(60.0));
+        assert_eq!(edid.model(), "DELL U2419H");
+        assert_eq!(edid.diagonal().map(|d| d.round()), Some(24.0));
+
+        // Invalid checksum
+        let mut data = edid_fixture();
+        data[100] ^= 0xFF;
+        assert_eq!(Edid::parse(&data), None);
+        assert_eq!(Edid::parse(&[]), None);
+    }
+
+    #[test]
+    fn test_get_monitors() {
+        let dir = fixture_dir("drm");
+        write_file(&dir, "card0/dev", "226:0\n");
+        write_file(&dir, "card0-DP-1/status", "disconnected\n");
+        write_file(&dir, "card0-DP-1/modes", "");
+        write_file(&dir, "card0-HDMI-A-1/status", "connected\n");
+        write_file(&dir, "card0-HDMI-A-1/modes", "2560x1440\n1920x1080\n");
+        write_file(&dir, "card0-HDMI-A-1/edid", edid_fixture());
+        write_file(&dir, "card1-eDP-1/status", "connected\n");
+        write_file(&dir, "card1-eDP-1/modes", "");
+        write_file(&dir, "card1-eDP-1/edid", edid_fixture());
+
+        let monitors = get_monitors(&dir);
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[0].connector, "HDMI-A-1");
+        assert_eq!(monitors[0].resolution, Some((2560, 1440)));
+        // The EDID timing is for another resolution than the preferred mode
+        assert_eq!(monitors[0].refresh_rate(), None);
+        // Resolution from the EDID when there are no modes
+        assert_eq!(monitors[1].connector, "eDP-1");
+        assert_eq!(monitors[1].resolution, Some((1920, 1080)));
+        assert_eq!(monitors[1].refresh_rate(), Some(60.0));
+    }
+}
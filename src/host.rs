@@ -0,0 +1,205 @@
+use std::fs::read_to_string;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::Module;
+
+/// Contains the hardware information from the DMI (SMBIOS) tables, one file
+/// per field
+const DMI_PATH: &str = "/sys/class/dmi/id";
+/// Model of ARM boards without DMI, like the Raspberry Pi. The value is null
+/// terminated.
+const DEVICE_TREE_MODEL_PATH: &str = "/proc/device-tree/model";
+
+/// Values that vendors leave in DMI fields they don't fill in
+const PLACEHOLDERS: &[&str] = &[
+    "To Be Filled By O.E.M.",
+    "To be filled by O.E.M.",
+    "O.E.M.",
+    "OEM",
+    "System Product Name",
+    "System Version",
+    "System manufacturer",
+    "System Manufacturer",
+    "Default string",
+    "Not Applicable",
+    "Not Specified",
+    "Type1ProductConfigId",
+    "All Series",
+    "None",
+    "INVALID",
+    "x.x",
+];
+
+#[derive(Debug, PartialEq)]
+struct HostInfo {
+    sys_vendor: Option<String>,
+    product_name: Option<String>,
+    product_version: Option<String>,
+    board_vendor: Option<String>,
+    board_name: Option<String>,
+    bios_version: Option<String>,
+    bios_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Host {
+    #[serde(skip)]
+    info: HostInfo,
+
+    header: String,
+    /// Whether to add the product version to the model
+    show_version: bool,
+
+    board_header: String,
+    /// Whether to show the motherboard on a separate line
+    show_board: bool,
+
+    bios_header: String,
+    /// Whether to show the BIOS version and date on a separate line
+    show_bios: bool,
+}
+
+/// Reads a hardware identifier, returning None if it's missing or a
+/// placeholder
+fn read_value(path: &Path) -> Option<String> {
+    let value = read_to_string(path).ok()?;
+    let value = value.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    if value.is_empty() || PLACEHOLDERS.contains(&value) {
+        return None;
+    }
+    Some(value.to_string())
+}
+
+fn read_field(dmi_path: &Path, field: &str) -> Option<String> {
+    read_value(&dmi_path.join(field))
+}
+
+fn get_host(dmi_path: &Path, device_tree_model_path: &Path) -> HostInfo {
+    let mut info = HostInfo {
+        sys_vendor: read_field(dmi_path, "sys_vendor"),
+        product_name: read_field(dmi_path, "product_name"),
+        product_version: read_field(dmi_path, "product_version"),
+        board_vendor: read_field(dmi_path, "board_vendor"),
+        board_name: read_field(dmi_path, "board_name"),
+        bios_version: read_field(dmi_path, "bios_version"),
+        bios_date: read_field(dmi_path, "bios_date"),
+    };
+
+    if info.product_name.is_none() {
+        info.product_name = read_value(device_tree_model_path);
+    }
+    info
+}
+
+/// Joins a vendor and a name, leaving out the vendor if the name already
+/// contains it
+fn join_vendor(vendor: &Option<String>, name: &str) -> String {
+    match vendor {
+        Some(vendor) if !name.starts_with(vendor.as_str()) => format!("{} {}", vendor, name),
+        _ => name.to_string(),
+    }
+}
+
+impl HostInfo {
+    fn model(&self, show_version: bool) -> Option<String> {
+        let mut model = join_vendor(&self.sys_vendor, self.product_name.as_ref()?);
+        if let (true, Some(version)) = (show_version, &self.product_version) {
+            model.push_str(&format!(" {}", version));
+        }
+        Some(model)
+    }
+
+    fn board(&self) -> Option<String> {
+        Some(join_vendor(&self.board_vendor, self.board_name.as_ref()?))
+    }
+
+    fn bios(&self) -> Option<String> {
+        let version = self.bios_version.as_ref()?;
+        match &self.bios_date {
+            Some(date) => Some(format!("{} ({})", version, date)),
+            None => Some(version.clone()),
+        }
+    }
+}
+
+impl Default for Host {
+    fn default() -> Self {
+        Self {
+            info: get_host(Path::new(DMI_PATH), Path::new(DEVICE_TREE_MODEL_PATH)),
+            header: String::from("Host"),
+            show_version: true,
+            board_header: String::from("Board"),
+            show_board: false,
+            bios_header: String::from("BIOS"),
+            show_bios: false,
+        }
+    }
+}
+
+impl Module for Host {
+    fn print(&self, config: &Config) {
+        if let Some(model) = self.info.model(config.host.show_version) {
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.host.header),
+                model
+            );
+        }
+        if let (true, Some(board)) = (config.host.show_board, self.info.board()) {
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.host.board_header),
+                board
+            );
+        }
+        if let (true, Some(bios)) = (config.host.show_bios, self.info.bios()) {
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.host.bios_header),
+                bios
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    #[test]
+    fn test_get_host() {
+        let dir = fixture_dir("dmi");
+        write_file(&dir, "id/sys_vendor", "LENOVO\n");
+        write_file(&dir, "id/product_name", "20XWCTO1WW\n");
+        write_file(&dir, "id/product_version", "ThinkPad X1 Carbon Gen 9\n");
+        write_file(&dir, "id/board_vendor", "To Be Filled By O.E.M.\n");
+        write_file(&dir, "id/board_name", "20XWCTO1WW\n");
+        write_file(&dir, "id/bios_version", "N32ET75W (1.51 )\n");
+
+        let info = get_host(&dir.join("id"), &dir.join("model"));
+        assert_eq!(info.board_vendor, None);
+        assert_eq!(
+            info.model(true),
+            Some(String::from("LENOVO 20XWCTO1WW ThinkPad X1 Carbon Gen 9"))
+        );
+        assert_eq!(info.board(), Some(String::from("20XWCTO1WW")));
+        assert_eq!(info.bios(), Some(String::from("N32ET75W (1.51 )")));
+    }
+
+    #[test]
+    fn test_device_tree_fallback() {
+        let dir = fixture_dir("device-tree");
+        write_file(&dir, "model", "Raspberry Pi 4 Model B Rev 1.4\0");
+
+        let info = get_host(&dir.join("id"), &dir.join("model"));
+        assert_eq!(
+            info.model(true),
+            Some(String::from("Raspberry Pi 4 Model B Rev 1.4"))
+        );
+    }
+}
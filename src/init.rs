@@ -0,0 +1,243 @@
+use std::fs::{read_link, read_to_string};
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread::{self, sleep};
+use std::time::{Duration, Instant};
+
+use ansi_term::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::process::PROC_PATH;
+use crate::Module;
+
+/// Created by OpenRC when it is running, to tell it apart from SysVinit as
+/// both use a binary called init
+const OPENRC_PATH: &str = "/run/openrc";
+
+/// Names of the init binaries and the init systems they belong to
+const INIT_SYSTEMS: &[(&str, &str)] = &[
+    ("systemd", "systemd"),
+    ("openrc-init", "OpenRC"),
+    ("runit", "runit"),
+    ("runit-init", "runit"),
+    ("s6-svscan", "s6"),
+    ("s6-linux-init", "s6"),
+    ("dinit", "dinit"),
+];
+
+/// Commands printing the version of an init system
+const VERSION_COMMANDS: &[(&str, &str, &str)] = &[
+    ("systemd", "systemctl", "--version"),
+    ("OpenRC", "openrc", "--version"),
+    ("dinit", "dinit", "--version"),
+];
+
+/// Identifies the init system from the name of PID 1 and its executable.
+/// The executable takes precedence, since /sbin/init is often a symlink to
+/// the binary of another init system.
+fn identify(comm: &str, exe_name: Option<&str>, openrc_running: bool) -> Option<String> {
+    let find = |name: &str| {
+        INIT_SYSTEMS
+            .iter()
+            .find(|(binary, _)| *binary == name)
+            .map(|(_, init)| init.to_string())
+    };
+    if let Some(init) = exe_name.and_then(find).or_else(|| find(comm)) {
+        return Some(init);
+    }
+    match exe_name.unwrap_or(comm) {
+        "init" if openrc_running => Some(String::from("OpenRC")),
+        "init" => Some(String::from("SysVinit")),
+        _ => None,
+    }
+}
+
+fn get_init(proc_path: &Path) -> Option<String> {
+    let dir = proc_path.join("1");
+    let comm = match read_to_string(dir.join("comm")) {
+        Ok(c) => c.trim().to_string(),
+        Err(e) => {
+            eprintln!("Failed to read name of PID 1, {}", e);
+            return None;
+        }
+    };
+    // Reading the link requires the same permissions as tracing PID 1, so it
+    // usually only works as root
+    let exe = read_link(dir.join("exe")).ok();
+    let exe_name = exe
+        .as_ref()
+        .and_then(|exe| exe.file_name()?.to_str())
+        .map(|name| name.trim_end_matches(" (deleted)"));
+    identify(&comm, exe_name, Path::new(OPENRC_PATH).exists())
+}
+
+/// Parses the version from the first line of a `--version` output, like
+/// "systemd 252 (252.22-1~deb12u1)" or "Dinit version 0.17.1."
+fn parse_version(output: &str) -> Option<String> {
+    let version = output
+        .lines()
+        .next()?
+        .split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?
+        .trim_end_matches(|c: char| !c.is_ascii_digit());
+    Some(version.to_string())
+}
+
+fn get_version(init: &str) -> Option<String> {
+    let (_, command, arg) = VERSION_COMMANDS.iter().find(|(name, _, _)| *name == init)?;
+    let output = Command::new(command).arg(arg).output().ok()?;
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Counts the failed units listed by systemctl. systemctl can hang when the
+/// bus is unresponsive, so it is killed after the timeout.
+fn get_failed_units(timeout: Duration) -> Option<usize> {
+    let mut child = Command::new("systemctl")
+        .args(["--failed", "--plain", "--no-legend"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    // Reads the output while waiting, as systemctl blocks once the pipe is
+    // full on systems with many units
+    let mut stdout = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if start.elapsed() < timeout => sleep(Duration::from_millis(10)),
+            _ => {
+                eprintln!("Timed out counting failed systemd units");
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+    // systemctl fails without a system bus, like in containers or under
+    // another init system
+    if !status.success() {
+        return None;
+    }
+
+    let output = reader.join().ok()?.ok()?;
+    Some(
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .count(),
+    )
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Init {
+    #[serde(skip)]
+    init: Option<String>,
+
+    header: String,
+    /// Whether to show the version, which requires running a command
+    show_version: bool,
+    /// Whether to count the failed units when systemd is the init system
+    show_failed_units: bool,
+    /// Time in milliseconds to wait for systemctl to list the failed units
+    failed_units_timeout: u64,
+}
+
+impl Default for Init {
+    fn default() -> Self {
+        Self {
+            init: get_init(Path::new(PROC_PATH)),
+            header: String::from("Init"),
+            show_version: true,
+            show_failed_units: false,
+            failed_units_timeout: 1000,
+        }
+    }
+}
+
+impl Module for Init {
+    fn print(&self, config: &Config) {
+        if let Some(init) = &self.init {
+            let mut output = init.clone();
+            if config.init.show_version {
+                if let Some(version) = get_version(init) {
+                    output.push_str(&format!(" {}", version));
+                }
+            }
+            if config.init.show_failed_units && init == "systemd" {
+                let timeout = Duration::from_millis(config.init.failed_units_timeout);
+                if let Some(count) = get_failed_units(timeout) {
+                    let units = if count == 1 { "unit" } else { "units" };
+                    let failed = format!("{} failed {}", count, units);
+                    if count > 0 {
+                        output.push_str(&format!(" ({})", Color::Red.paint(failed)));
+                    } else {
+                        output.push_str(&format!(" ({})", failed));
+                    }
+                }
+            }
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.init.header),
+                output
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    #[test]
+    fn test_identify() {
+        assert_eq!(
+            identify("systemd", None, false),
+            Some(String::from("systemd"))
+        );
+        // /sbin/init linking to systemd, as on Debian
+        assert_eq!(
+            identify("init", Some("systemd"), false),
+            Some(String::from("systemd"))
+        );
+        assert_eq!(identify("runit", None, false), Some(String::from("runit")));
+        assert_eq!(identify("s6-svscan", None, false), Some(String::from("s6")));
+        assert_eq!(identify("init", None, true), Some(String::from("OpenRC")));
+        assert_eq!(
+            identify("init", None, false),
+            Some(String::from("SysVinit"))
+        );
+        // Containers often run a program as PID 1
+        assert_eq!(identify("bash", None, false), None);
+
+        let dir = fixture_dir("init");
+        write_file(&dir, "1/comm", "dinit\n");
+        assert_eq!(get_init(&dir), Some(String::from("dinit")));
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            parse_version("systemd 252 (252.22-1~deb12u1)\n+PAM +AUDIT +SELINUX"),
+            Some(String::from("252"))
+        );
+        assert_eq!(
+            parse_version("openrc (OpenRC) 0.45.2 (Gentoo Linux)\n"),
+            Some(String::from("0.45.2"))
+        );
+        assert_eq!(
+            parse_version("Dinit version 0.17.1.\n"),
+            Some(String::from("0.17.1"))
+        );
+        assert_eq!(parse_version(""), None);
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs::read_to_string;
+use std::path::Path;
+
+use ansi_term::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::process::{process_dirs, PROC_PATH};
+use crate::Module;
+
+/// /proc/loadavg contains the 1, 5 and 15 minute load averages, the number of
+/// running and total tasks, and the last PID used, like:
+/// 0.52 0.58 0.59 2/1123 45678
+const LOADAVG_PATH: &str = "/proc/loadavg";
+
+#[derive(Debug, PartialEq)]
+struct LoadAverage {
+    averages: [f32; 3],
+    running_tasks: u32,
+    total_tasks: u32,
+    last_pid: u32,
+}
+
+fn parse(contents: &str) -> Option<LoadAverage> {
+    let fields = contents.split_whitespace().collect::<Vec<_>>();
+    let (running_tasks, total_tasks) = fields.get(3)?.split_once('/')?;
+    Some(LoadAverage {
+        averages: [
+            fields.first()?.parse().ok()?,
+            fields.get(1)?.parse().ok()?,
+            fields.get(2)?.parse().ok()?,
+        ],
+        running_tasks: running_tasks.parse().ok()?,
+        total_tasks: total_tasks.parse().ok()?,
+        last_pid: fields.get(4)?.parse().ok()?,
+    })
+}
+
+fn get_load() -> Option<LoadAverage> {
+    let contents = match read_to_string(LOADAVG_PATH) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Failed to read {}, {}", LOADAVG_PATH, e);
+            return None;
+        }
+    };
+    parse(&contents)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Load {
+    #[serde(skip)]
+    load: Option<LoadAverage>,
+
+    header: String,
+    /// Whether to show the number of running and total tasks
+    show_tasks: bool,
+    /// Whether to show the last PID used
+    show_last_pid: bool,
+    /// Whether to color load averages exceeding the cpu core count red
+    color_high_load: bool,
+}
+
+impl Default for Load {
+    fn default() -> Self {
+        Self {
+            load: get_load(),
+            header: String::from("Load"),
+            show_tasks: false,
+            show_last_pid: false,
+            color_high_load: true,
+        }
+    }
+}
+
+impl Module for Load {
+    fn print(&self, config: &Config) {
+        if let Some(load) = &self.load {
+            let cores = config.cpu.cores() as f32;
+            let mut output = load
+                .averages
+                .iter()
+                .map(|avg| {
+                    let avg_str = format!("{:.2}", avg);
+                    if config.load.color_high_load && cores > 0.0 && *avg > cores {
+                        Color::Red.paint(avg_str).to_string()
+                    } else {
+                        avg_str
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let mut details = Vec::new();
+            if config.load.show_tasks {
+                details.push(format!("{}/{} tasks", load.running_tasks, load.total_tasks));
+            }
+            if config.load.show_last_pid {
+                details.push(format!("last PID {}", load.last_pid));
+            }
+            if !details.is_empty() {
+                output.push_str(&format!(" ({})", details.join(", ")));
+            }
+
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.load.header),
+                output
+            );
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Processes {
+    #[serde(skip)]
+    count: usize,
+
+    header: String,
+}
+
+impl Default for Processes {
+    fn default() -> Self {
+        Self {
+            count: process_dirs(Path::new(PROC_PATH)).len(),
+            header: String::from("Processes"),
+        }
+    }
+}
+
+impl Module for Processes {
+    fn print(&self, config: &Config) {
+        println!(
+            "{}: {}",
+            config.color.bold().paint(&config.processes.header),
+            self.count
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_parse_loadavg() {
+        assert_eq!(
+            parse("0.52 0.58 0.59 2/1123 45678\n"),
+            Some(LoadAverage {
+                averages: [0.52, 0.58, 0.59],
+                running_tasks: 2,
+                total_tasks: 1123,
+                last_pid: 45678,
+            })
+        );
+        assert_eq!(parse(""), None);
+    }
+}
@@ -0,0 +1,440 @@
+use std::env;
+use std::fs::{read_link, read_to_string};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::tzif::local_offset;
+use crate::Module;
+
+/// System wide locale, set by systemd-localed
+const LOCALE_CONF_PATH: &str = "/etc/locale.conf";
+/// Name of the time zone on Debian based distributions
+const TIMEZONE_PATH: &str = "/etc/timezone";
+/// Time zone data, usually a symlink into /usr/share/zoneinfo
+const LOCALTIME_PATH: &str = "/etc/localtime";
+/// Console keymap and X11 layout, set by systemd-localed
+const VCONSOLE_CONF_PATH: &str = "/etc/vconsole.conf";
+/// X11 layout on Debian based distributions
+const DEFAULT_KEYBOARD_PATH: &str = "/etc/default/keyboard";
+
+/// Locale categories that can override LANG
+const LOCALE_CATEGORIES: &[&str] = &[
+    "LC_CTYPE",
+    "LC_NUMERIC",
+    "LC_TIME",
+    "LC_COLLATE",
+    "LC_MONETARY",
+    "LC_MESSAGES",
+    "LC_PAPER",
+    "LC_NAME",
+    "LC_ADDRESS",
+    "LC_TELEPHONE",
+    "LC_MEASUREMENT",
+    "LC_IDENTIFICATION",
+];
+
+/// Parses shell style variable assignments like KEYMAP="de-latin1", ignoring
+/// comments
+fn parse_variables(contents: &str) -> Vec<(&str, &str)> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            (key.trim(), value)
+        })
+        .collect()
+}
+
+fn variable<'a>(variables: &[(&str, &'a str)], name: &str) -> Option<&'a str> {
+    variables
+        .iter()
+        .rev()
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| *value)
+        .filter(|value| !value.is_empty())
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+#[derive(Debug, PartialEq)]
+struct LocaleSettings {
+    lang: Option<String>,
+    /// Categories set to something other than LANG, like LC_TIME=de_DE.UTF-8
+    categories: Vec<(String, String)>,
+}
+
+/// Resolves the locale from the environment, falling back to locale.conf.
+/// LC_ALL overrides LANG and all categories.
+fn resolve_locale(env_var: impl Fn(&str) -> Option<String>, locale_conf: &str) -> LocaleSettings {
+    let conf = parse_variables(locale_conf);
+    let lookup = |name: &str| env_var(name).or_else(|| variable(&conf, name).map(String::from));
+
+    if let Some(all) = env_var("LC_ALL") {
+        return LocaleSettings {
+            lang: Some(all),
+            categories: Vec::new(),
+        };
+    }
+    let lang = lookup("LANG");
+    let categories = LOCALE_CATEGORIES
+        .iter()
+        .filter_map(|category| Some((category.to_string(), lookup(category)?)))
+        .filter(|(_, value)| Some(value) != lang.as_ref())
+        .collect();
+    LocaleSettings { lang, categories }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Locale {
+    #[serde(skip)]
+    settings: LocaleSettings,
+
+    header: String,
+    /// Whether to add the categories that override LANG
+    show_categories: bool,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            settings: resolve_locale(
+                env_var,
+                &read_to_string(LOCALE_CONF_PATH).unwrap_or_default(),
+            ),
+            header: String::from("Locale"),
+            show_categories: false,
+        }
+    }
+}
+
+impl Module for Locale {
+    fn print(&self, config: &Config) {
+        let mut locale = match &self.settings.lang {
+            Some(lang) => lang.clone(),
+            None => return,
+        };
+        if config.locale.show_categories && !self.settings.categories.is_empty() {
+            let categories = self
+                .settings
+                .categories
+                .iter()
+                .map(|(category, value)| format!("{}={}", category, value))
+                .collect::<Vec<_>>();
+            locale.push_str(&format!(" ({})", categories.join(", ")));
+        }
+        println!(
+            "{}: {}",
+            config.color.bold().paint(&config.locale.header),
+            locale
+        );
+    }
+}
+
+/// Returns the zone name from a path into the time zone database, like
+/// /usr/share/zoneinfo/Europe/Berlin
+fn zone_from_path(path: &str) -> Option<String> {
+    let (_, zone) = path.split_once("zoneinfo/")?;
+    // Some distributions have posix/ and right/ variants of the database
+    let zone = zone
+        .strip_prefix("posix/")
+        .or_else(|| zone.strip_prefix("right/"))
+        .unwrap_or(zone);
+    Some(zone.to_string())
+}
+
+/// Returns the time zone name from TZ, which is like Europe/Berlin or
+/// :Europe/Berlin, or a path to a time zone file
+fn zone_from_tz(tz: &str) -> String {
+    let tz = tz.strip_prefix(':').unwrap_or(tz);
+    match tz.starts_with('/') {
+        true => zone_from_path(tz).unwrap_or_else(|| tz.to_string()),
+        false => tz.to_string(),
+    }
+}
+
+fn get_timezone() -> Option<String> {
+    if let Some(tz) = env_var("TZ") {
+        return Some(zone_from_tz(&tz));
+    }
+    let from_file = read_to_string(TIMEZONE_PATH)
+        .ok()
+        .map(|zone| zone.trim().to_string())
+        .filter(|zone| !zone.is_empty());
+    from_file.or_else(|| {
+        let target = read_link(LOCALTIME_PATH).ok()?;
+        zone_from_path(&target.to_string_lossy())
+    })
+}
+
+/// Formats an offset from UTC in seconds like UTC+05:30
+fn format_offset(offset: i64) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    format!("UTC{}{:02}:{:02}", sign, offset / 3600, offset / 60 % 60)
+}
+
+/// Returns the time zone like "Asia/Kolkata (UTC+05:30, IST)". Without a
+/// zone name the abbreviation or the offset is used as the name, unless the
+/// system has no time zone configuration at all and uses UTC.
+fn format_timezone(
+    zone: Option<&str>,
+    has_localtime: bool,
+    offset: i64,
+    abbreviation: &str,
+    show_offset: bool,
+) -> String {
+    // Zones without an abbreviation have names like +0530
+    let has_abbreviation = !abbreviation.is_empty() && !abbreviation.starts_with(['+', '-']);
+    let mut timezone = match zone {
+        Some(zone) => zone.to_string(),
+        None if !has_localtime => String::from("UTC"),
+        None if has_abbreviation => abbreviation.to_string(),
+        None => format_offset(offset),
+    };
+    if show_offset {
+        let mut details = Vec::new();
+        if format_offset(offset) != timezone {
+            details.push(format_offset(offset));
+        }
+        if has_abbreviation && abbreviation != timezone {
+            details.push(abbreviation.to_string());
+        }
+        if !details.is_empty() {
+            timezone.push_str(&format!(" ({})", details.join(", ")));
+        }
+    }
+    timezone
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timezone {
+    #[serde(skip)]
+    zone: Option<String>,
+
+    header: String,
+    /// Whether to add the current offset from UTC and the zone abbreviation
+    show_offset: bool,
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Self {
+            zone: get_timezone(),
+            header: String::from("Timezone"),
+            show_offset: true,
+        }
+    }
+}
+
+impl Module for Timezone {
+    fn print(&self, config: &Config) {
+        let (offset, abbreviation) = local_offset(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs() as i64),
+        );
+        let timezone = format_timezone(
+            self.zone.as_deref(),
+            Path::new(LOCALTIME_PATH).exists(),
+            offset,
+            &abbreviation,
+            config.timezone.show_offset,
+        );
+        println!(
+            "{}: {}",
+            config.color.bold().paint(&config.timezone.header),
+            timezone
+        );
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Layout {
+    layout: String,
+    variant: Option<String>,
+}
+
+/// Finds the keyboard layout. XKB_DEFAULT_LAYOUT is used by Wayland
+/// compositors, /etc/default/keyboard and vconsole.conf by X11 and the
+/// console. The console keymap is the last resort, as its names differ from
+/// XKB layouts.
+fn resolve_layout(
+    env_var: impl Fn(&str) -> Option<String>,
+    default_keyboard: &str,
+    vconsole_conf: &str,
+) -> Option<Layout> {
+    if let Some(layout) = env_var("XKB_DEFAULT_LAYOUT") {
+        return Some(Layout {
+            layout,
+            variant: env_var("XKB_DEFAULT_VARIANT"),
+        });
+    }
+    let default_keyboard = parse_variables(default_keyboard);
+    let vconsole_conf = parse_variables(vconsole_conf);
+    [&default_keyboard, &vconsole_conf]
+        .iter()
+        .find_map(|variables| {
+            Some(Layout {
+                layout: variable(variables, "XKBLAYOUT")?.to_string(),
+                variant: variable(variables, "XKBVARIANT").map(String::from),
+            })
+        })
+        .or_else(|| {
+            Some(Layout {
+                layout: variable(&vconsole_conf, "KEYMAP")?.to_string(),
+                variant: None,
+            })
+        })
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keyboard {
+    #[serde(skip)]
+    layout: Option<Layout>,
+
+    header: String,
+    /// Whether to add the layout variant, like intl or nodeadkeys
+    show_variant: bool,
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Self {
+            layout: resolve_layout(
+                env_var,
+                &read_to_string(DEFAULT_KEYBOARD_PATH).unwrap_or_default(),
+                &read_to_string(VCONSOLE_CONF_PATH).unwrap_or_default(),
+            ),
+            header: String::from("Keyboard"),
+            show_variant: true,
+        }
+    }
+}
+
+impl Module for Keyboard {
+    fn print(&self, config: &Config) {
+        if let Some(layout) = &self.layout {
+            let mut keyboard = layout.layout.clone();
+            if let (true, Some(variant)) = (config.keyboard.show_variant, &layout.variant) {
+                keyboard.push_str(&format!(" ({})", variant));
+            }
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.keyboard.header),
+                keyboard
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| variable(vars, name).map(String::from)
+    }
+
+    #[test]
+    fn test_resolve_locale() {
+        let locale_conf = "LANG=en_US.UTF-8\nLC_TIME=en_DK.UTF-8\nLC_PAPER=en_US.UTF-8\n";
+        assert_eq!(
+            resolve_locale(env(&[]), locale_conf),
+            LocaleSettings {
+                lang: Some(String::from("en_US.UTF-8")),
+                categories: vec![(String::from("LC_TIME"), String::from("en_DK.UTF-8"))],
+            }
+        );
+        // The environment takes precedence over locale.conf
+        assert_eq!(
+            resolve_locale(env(&[("LANG", "de_DE.UTF-8")]), locale_conf).lang,
+            Some(String::from("de_DE.UTF-8"))
+        );
+        assert_eq!(
+            resolve_locale(env(&[("LC_ALL", "C")]), locale_conf),
+            LocaleSettings {
+                lang: Some(String::from("C")),
+                categories: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_timezone_names() {
+        assert_eq!(
+            zone_from_path("/usr/share/zoneinfo/America/New_York"),
+            Some(String::from("America/New_York"))
+        );
+        assert_eq!(
+            zone_from_path("../usr/share/zoneinfo/posix/Asia/Kolkata"),
+            Some(String::from("Asia/Kolkata"))
+        );
+        assert_eq!(zone_from_tz(":Europe/Berlin"), "Europe/Berlin");
+        assert_eq!(format_offset(19800), "UTC+05:30");
+        assert_eq!(format_offset(-12600), "UTC-03:30");
+    }
+
+    #[test]
+    fn test_format_timezone() {
+        assert_eq!(
+            format_timezone(Some("Asia/Kolkata"), true, 19800, "IST", true),
+            "Asia/Kolkata (UTC+05:30, IST)"
+        );
+        // /etc/localtime is a copy rather than a symlink
+        assert_eq!(
+            format_timezone(None, true, 7200, "CEST", true),
+            "CEST (UTC+02:00)"
+        );
+        assert_eq!(
+            format_timezone(None, true, -10800, "-03", true),
+            "UTC-03:00"
+        );
+        // No time zone configuration
+        assert_eq!(
+            format_timezone(None, false, 0, "UTC", true),
+            "UTC (UTC+00:00)"
+        );
+        assert_eq!(format_timezone(None, true, 7200, "CEST", false), "CEST");
+    }
+
+    #[test]
+    fn test_resolve_layout() {
+        let default_keyboard = "# KEYBOARD CONFIGURATION FILE
+XKBMODEL=\"pc105\"
+XKBLAYOUT=\"us\"
+XKBVARIANT=\"intl\"
+XKBOPTIONS=\"\"
+";
+        assert_eq!(
+            resolve_layout(env(&[]), default_keyboard, ""),
+            Some(Layout {
+                layout: String::from("us"),
+                variant: Some(String::from("intl")),
+            })
+        );
+        assert_eq!(
+            resolve_layout(env(&[]), "", "KEYMAP=de-latin1\nFONT=eurlatgr\n"),
+            Some(Layout {
+                layout: String::from("de-latin1"),
+                variant: None,
+            })
+        );
+        assert_eq!(
+            resolve_layout(env(&[("XKB_DEFAULT_LAYOUT", "fr")]), default_keyboard, "")
+                .map(|l| l.layout),
+            Some(String::from("fr"))
+        );
+        assert_eq!(resolve_layout(env(&[]), "", ""), None);
+    }
+}
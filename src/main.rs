@@ -1,11 +1,34 @@
+mod battery;
+mod calendar;
 mod config;
 mod cpu;
+mod cpu_usage;
+mod datetime;
+mod desktop;
+mod display;
+mod host;
+mod init;
+mod load;
+mod locale;
 mod memory;
+mod network;
+#[cfg(feature = "nl80211")]
+mod nl80211;
 mod os;
 mod packages;
+mod process;
+mod sensors;
 mod shell;
+mod terminal;
+mod terminal_font;
+#[cfg(test)]
+mod test_utils;
+mod theme;
 mod title;
+mod tzif;
 mod uptime;
+mod users;
+mod virtualization;
 
 use config::Config;
 
@@ -23,19 +46,45 @@ impl SystemInfo {
         let modules: Vec<Box<dyn Module>> = vec![
             Box::new(title::Title::default()),
             Box::new(os::Distro::default()),
+            Box::new(host::Host::default()),
+            Box::new(virtualization::Virtualization::default()),
             Box::new(os::Architechture::default()),
             Box::new(os::Kernel::default()),
             Box::new(uptime::Uptime::default()),
+            Box::new(init::Init::default()),
             Box::new(packages::Packages::default()),
             Box::new(shell::Shell::default()),
+            Box::new(display::Display::default()),
+            Box::new(desktop::Desktop::default()),
+            Box::new(desktop::WindowManager::default()),
+            Box::new(theme::GtkTheme::default()),
+            Box::new(theme::Icons::default()),
+            Box::new(theme::Cursor::default()),
+            Box::new(terminal::Terminal::default()),
+            Box::new(terminal_font::TerminalFont::default()),
             Box::new(memory::Memory::default()),
             Box::new(cpu::Cpu::default()),
+            Box::new(cpu_usage::CpuUsage::default()),
+            Box::new(load::Load::default()),
+            Box::new(load::Processes::default()),
+            Box::new(users::Users::default()),
+            Box::new(sensors::Sensors::default()),
+            Box::new(battery::Battery::default()),
+            Box::new(network::Network::default()),
+            Box::new(locale::Locale::default()),
+            Box::new(locale::Timezone::default()),
+            Box::new(datetime::DateTime::default()),
+            Box::new(locale::Keyboard::default()),
         ];
         SystemInfo { modules }
     }
 }
 
 fn main() {
+    // Loading the configuration and the modules overlaps with the cpu usage
+    // sampling interval
+    cpu_usage::take_first_sample();
+
     let config = match Config::load() {
         Ok(c) => c,
         Err(e) => {
@@ -0,0 +1,410 @@
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs::{read_dir, read_link, read_to_string};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::ptr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::Module;
+
+/// Contains a directory for each network interface, with the files operstate,
+/// address (MAC) and speed (Mb/s)
+const NET_PATH: &str = "/sys/class/net";
+/// Kernel routing table. The interface of the default route (destination
+/// 00000000) is the primary interface.
+const ROUTE_PATH: &str = "/proc/net/route";
+/// Link quality and signal level of each wireless interface
+const WIRELESS_PATH: &str = "/proc/net/wireless";
+/// Maximum link quality reported by most drivers
+const MAX_LINK_QUALITY: f32 = 70.0;
+
+#[derive(Debug, Default, PartialEq)]
+struct Wireless {
+    /// Link quality in percent
+    quality: Option<u32>,
+    /// Signal level in dBm
+    signal: Option<i32>,
+    ssid: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Interface {
+    name: String,
+    /// up, down, dormant or unknown
+    operstate: String,
+    mac: Option<String>,
+    /// Speed in Mb/s
+    speed: Option<u32>,
+    /// Whether the interface is virtual, like lo or docker0
+    is_virtual: bool,
+    /// Addresses with their prefix length
+    addresses: Vec<(IpAddr, u8)>,
+    /// None for wired interfaces
+    wireless: Option<Wireless>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Network {
+    #[serde(skip)]
+    interfaces: Vec<Interface>,
+    #[serde(skip)]
+    primary_interface: Option<String>,
+
+    header: String,
+    /// Whether to only show the interface of the default route
+    only_primary: bool,
+    /// Whether to show the loopback interface
+    show_loopback: bool,
+    /// Whether to show virtual interfaces, like bridges and VPN tunnels
+    show_virtual: bool,
+    /// Interfaces starting with these prefixes are hidden
+    hidden_prefixes: Vec<String>,
+    /// Whether to show IPv6 addresses
+    show_ipv6: bool,
+    /// Whether to show the MAC address
+    show_mac: bool,
+    /// Whether to show the operstate and link speed
+    show_state: bool,
+    /// Whether to show the SSID, link quality and signal level of wireless
+    /// interfaces
+    show_wireless: bool,
+}
+
+/// Parses the interface of the default route with the lowest metric from
+/// /proc/net/route. Content is in the form:
+/// Iface  Destination  Gateway  Flags  RefCnt  Use  Metric  Mask ...
+fn parse_route(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let metric = fields.get(6)?.parse::<u32>().ok()?;
+            match fields.get(1) {
+                Some(&"00000000") => Some((metric, fields[0])),
+                _ => None,
+            }
+        })
+        .min()
+        .map(|(_, iface)| iface.to_string())
+}
+
+/// Parses the link quality and signal level of each interface from
+/// /proc/net/wireless. Content is in the form:
+/// Inter-| sta-|   Quality        |   Discarded packets ...
+///  face | tus | link level noise |  nwid  crypt   frag ...
+///  wlan0: 0000   54.  -56.  -256        0      0      0 ...
+fn parse_wireless(contents: &str) -> HashMap<&str, Wireless> {
+    contents
+        .lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (name, values) = line.split_once(':')?;
+            let values = values
+                .split_whitespace()
+                .map(|v| v.trim_end_matches('.').parse::<f32>().ok())
+                .collect::<Vec<_>>();
+            let quality = values
+                .get(1)
+                .copied()
+                .flatten()
+                .map(|link| (link / MAX_LINK_QUALITY * 100.0).round().min(100.0) as u32);
+            // Some drivers report the level as an unsigned byte
+            let signal = values.get(2).copied().flatten().map(|level| {
+                let level = level as i32;
+                if level >= 128 {
+                    level - 256
+                } else {
+                    level
+                }
+            });
+            let wireless = Wireless {
+                quality,
+                signal,
+                ssid: None,
+            };
+            Some((name.trim(), wireless))
+        })
+        .collect()
+}
+
+/// Reads the interfaces from a directory like /sys/class/net
+fn get_interfaces(path: &Path) -> Vec<Interface> {
+    let mut dirs = match read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect::<Vec<_>>(),
+        Err(_) => return Vec::new(),
+    };
+    dirs.sort();
+
+    dirs.iter()
+        .filter_map(|dir| {
+            let read = |file: &str| read_to_string(dir.join(file)).ok();
+            let name = dir.file_name()?.to_str()?.to_string();
+            Some(Interface {
+                operstate: read("operstate")
+                    .map_or(String::from("unknown"), |s| s.trim().to_string()),
+                mac: read("address")
+                    .map(|a| a.trim().to_string())
+                    .filter(|a| !a.is_empty() && a != "00:00:00:00:00:00"),
+                // Reading the speed fails or gives -1 when the link is down
+                speed: read("speed").and_then(|s| s.trim().parse().ok()),
+                // Virtual interfaces link to /sys/devices/virtual/net
+                is_virtual: read_link(dir)
+                    .map(|target| target.to_string_lossy().contains("/virtual/"))
+                    .unwrap_or(false),
+                addresses: Vec::new(),
+                // Only wireless interfaces have a wireless directory
+                wireless: if dir.join("wireless").exists() {
+                    Some(Wireless::default())
+                } else {
+                    None
+                },
+                name,
+            })
+        })
+        .collect()
+}
+
+/// Returns the prefix length of a netmask like 255.255.255.0
+fn prefix_length(netmask: &[u8]) -> u8 {
+    netmask.iter().map(|b| b.count_ones() as u8).sum()
+}
+
+/// Returns the addresses of each interface from getifaddrs
+fn get_addresses() -> HashMap<String, Vec<(IpAddr, u8)>> {
+    let mut addresses: HashMap<String, Vec<(IpAddr, u8)>> = HashMap::new();
+    let mut ifaddrs: *mut libc::ifaddrs = ptr::null_mut();
+
+    // SAFETY: getifaddrs returns a linked list that stays valid until it is
+    // freed with freeifaddrs. The address structs are cast based on their
+    // family, as described in getifaddrs(3).
+    unsafe {
+        if libc::getifaddrs(&mut ifaddrs) != 0 {
+            eprintln!("Failed to get network addresses");
+            return addresses;
+        }
+
+        let mut current = ifaddrs;
+        while !current.is_null() {
+            let ifa = &*current;
+            current = ifa.ifa_next;
+            if ifa.ifa_addr.is_null() || ifa.ifa_netmask.is_null() {
+                continue;
+            }
+
+            let address = match (*ifa.ifa_addr).sa_family as i32 {
+                libc::AF_INET => {
+                    let addr = &*(ifa.ifa_addr as *const libc::sockaddr_in);
+                    let mask = &*(ifa.ifa_netmask as *const libc::sockaddr_in);
+                    let ip = Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr));
+                    let prefix = prefix_length(&mask.sin_addr.s_addr.to_ne_bytes());
+                    (IpAddr::V4(ip), prefix)
+                }
+                libc::AF_INET6 => {
+                    let addr = &*(ifa.ifa_addr as *const libc::sockaddr_in6);
+                    let mask = &*(ifa.ifa_netmask as *const libc::sockaddr_in6);
+                    let ip = Ipv6Addr::from(addr.sin6_addr.s6_addr);
+                    (IpAddr::V6(ip), prefix_length(&mask.sin6_addr.s6_addr))
+                }
+                _ => continue,
+            };
+
+            let name = CStr::from_ptr(ifa.ifa_name).to_string_lossy().to_string();
+            addresses.entry(name).or_default().push(address);
+        }
+
+        libc::freeifaddrs(ifaddrs);
+    }
+    addresses
+}
+
+impl Network {
+    /// Returns the interfaces to show with the current configuration
+    fn shown_interfaces(&self, config: &Network) -> Vec<&Interface> {
+        self.interfaces
+            .iter()
+            .filter(|iface| {
+                if config.only_primary {
+                    return self.primary_interface.as_ref() == Some(&iface.name);
+                }
+                let is_loopback = iface.name == "lo";
+                (config.show_loopback || !is_loopback)
+                    && (config.show_virtual || !iface.is_virtual || is_loopback)
+                    && !config
+                        .hidden_prefixes
+                        .iter()
+                        .any(|prefix| iface.name.starts_with(prefix.as_str()))
+            })
+            .collect()
+    }
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        let mut interfaces = get_interfaces(Path::new(NET_PATH));
+        let mut addresses = get_addresses();
+        let wireless_contents = read_to_string(WIRELESS_PATH).unwrap_or_default();
+        let mut wireless = parse_wireless(&wireless_contents);
+        for iface in interfaces.iter_mut() {
+            iface.addresses = addresses.remove(&iface.name).unwrap_or_default();
+            if iface.wireless.is_some() {
+                let details = wireless.remove(iface.name.as_str()).unwrap_or_default();
+                #[cfg(feature = "nl80211")]
+                let details = Wireless {
+                    ssid: crate::nl80211::get_ssid(&iface.name),
+                    ..details
+                };
+                iface.wireless = Some(details);
+            }
+        }
+
+        Self {
+            interfaces,
+            primary_interface: read_to_string(ROUTE_PATH)
+                .ok()
+                .and_then(|contents| parse_route(&contents)),
+            header: String::from("Network"),
+            only_primary: true,
+            show_loopback: false,
+            show_virtual: false,
+            hidden_prefixes: ["docker", "veth", "br-", "virbr", "vnet"]
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            show_ipv6: false,
+            show_mac: false,
+            show_state: false,
+            show_wireless: true,
+        }
+    }
+}
+
+impl Module for Network {
+    fn print(&self, config: &Config) {
+        for iface in self.shown_interfaces(&config.network) {
+            let addresses = iface
+                .addresses
+                .iter()
+                .filter(|(ip, _)| config.network.show_ipv6 || ip.is_ipv4())
+                .map(|(ip, prefix)| format!("{}/{}", ip, prefix))
+                .collect::<Vec<_>>();
+            let mut network = iface.name.clone();
+            if !addresses.is_empty() {
+                network.push_str(&format!(" {}", addresses.join(", ")));
+            }
+
+            let mut details = Vec::new();
+            if config.network.show_state {
+                details.push(iface.operstate.clone());
+                if let Some(speed) = iface.speed.filter(|s| *s > 0) {
+                    details.push(format!("{} Mb/s", speed));
+                }
+            }
+            if let (true, Some(wireless)) = (config.network.show_wireless, &iface.wireless) {
+                if let Some(ssid) = &wireless.ssid {
+                    details.push(ssid.clone());
+                }
+                if let Some(quality) = wireless.quality {
+                    details.push(format!("{}%", quality));
+                }
+                if let Some(signal) = wireless.signal {
+                    details.push(format!("{} dBm", signal));
+                }
+            }
+            if let (true, Some(mac)) = (config.network.show_mac, &iface.mac) {
+                details.push(mac.clone());
+            }
+            if !details.is_empty() {
+                network.push_str(&format!(" ({})", details.join(", ")));
+            }
+
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.network.header),
+                network
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    #[test]
+    fn test_parse_route() {
+        let input =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0
+eth0\t00000000\t0100000A\t0003\t0\t0\t100\t00000000\t0\t0\t0
+eth0\t0000000A\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+";
+        assert_eq!(parse_route(input), Some(String::from("eth0")));
+        assert_eq!(parse_route("Iface\tDestination\n"), None);
+    }
+
+    #[test]
+    fn test_get_interfaces() {
+        let dir = fixture_dir("net");
+        write_file(&dir, "eth0/operstate", "up\n");
+        write_file(&dir, "eth0/address", "02:fc:00:00:00:01\n");
+        write_file(&dir, "eth0/speed", "1000\n");
+        write_file(&dir, "lo/operstate", "unknown\n");
+        write_file(&dir, "lo/address", "00:00:00:00:00:00\n");
+        write_file(&dir, "wlan0/operstate", "down\n");
+        write_file(&dir, "wlan0/speed", "-1\n");
+        write_file(&dir, "wlan0/wireless/uevent", "");
+
+        let interfaces = get_interfaces(&dir);
+        assert_eq!(interfaces.len(), 3);
+        assert_eq!(
+            interfaces[0],
+            Interface {
+                name: String::from("eth0"),
+                operstate: String::from("up"),
+                mac: Some(String::from("02:fc:00:00:00:01")),
+                speed: Some(1000),
+                is_virtual: false,
+                addresses: Vec::new(),
+                wireless: None,
+            }
+        );
+        assert_eq!(interfaces[1].mac, None);
+        assert_eq!(interfaces[2].speed, None);
+        assert_eq!(interfaces[2].wireless, Some(Wireless::default()));
+    }
+
+    #[test]
+    fn test_parse_wireless() {
+        let input =
+            "Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+ wlan0: 0000   56.  -54.  -256        0      0      0      0     18        0
+ wlan1: 0000   35   200   0           0      0      0      0      0        0
+";
+        let wireless = parse_wireless(input);
+        assert_eq!(
+            wireless.get("wlan0"),
+            Some(&Wireless {
+                quality: Some(80),
+                signal: Some(-54),
+                ssid: None,
+            })
+        );
+        assert_eq!(wireless.get("wlan1").and_then(|w| w.signal), Some(-56));
+        assert_eq!(wireless.get("wlan1").and_then(|w| w.quality), Some(50));
+    }
+
+    #[test]
+    fn test_prefix_length() {
+        assert_eq!(prefix_length(&[255, 255, 255, 0]), 24);
+        assert_eq!(prefix_length(&[255, 255, 240, 0]), 20);
+    }
+}
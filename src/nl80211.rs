@@ -0,0 +1,217 @@
+use std::ffi::CString;
+use std::io;
+use std::mem;
+
+/// Generic netlink controller, used to look up the id of the nl80211 family
+const GENL_ID_CTRL: u16 = 0x10;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+const NL80211_CMD_GET_INTERFACE: u8 = 5;
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_SSID: u16 = 52;
+
+const NLM_F_REQUEST: u16 = 1;
+const NLMSG_ERROR: u16 = 2;
+/// Size of struct nlmsghdr
+const NLMSG_HDRLEN: usize = 16;
+/// Size of struct genlmsghdr
+const GENL_HDRLEN: usize = 4;
+/// Size of struct nlattr
+const NLA_HDRLEN: usize = 4;
+/// Flags in the attribute type for nested attributes and byte order
+const NLA_TYPE_MASK: u16 = 0x3FFF;
+
+/// Netlink messages and attributes are padded to 4 bytes
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// Builds a generic netlink request with the given attributes
+fn build_request(family: u16, command: u8, seq: u32, attrs: &[(u16, &[u8])]) -> Vec<u8> {
+    let mut msg = vec![0u8; NLMSG_HDRLEN];
+    msg[4..6].copy_from_slice(&family.to_ne_bytes());
+    msg[6..8].copy_from_slice(&NLM_F_REQUEST.to_ne_bytes());
+    msg[8..12].copy_from_slice(&seq.to_ne_bytes());
+    // Command and version, followed by two reserved bytes
+    msg.extend_from_slice(&[command, 1, 0, 0]);
+
+    for (attr_type, payload) in attrs.iter() {
+        let len = NLA_HDRLEN + payload.len();
+        msg.extend_from_slice(&(len as u16).to_ne_bytes());
+        msg.extend_from_slice(&attr_type.to_ne_bytes());
+        msg.extend_from_slice(payload);
+        msg.resize(align(msg.len()), 0);
+    }
+
+    let len = msg.len() as u32;
+    msg[0..4].copy_from_slice(&len.to_ne_bytes());
+    msg
+}
+
+/// Parses the attributes following the generic netlink header of a reply
+fn parse_attrs(mut data: &[u8]) -> Vec<(u16, &[u8])> {
+    let mut attrs = Vec::new();
+    while data.len() >= NLA_HDRLEN {
+        let len = u16::from_ne_bytes([data[0], data[1]]) as usize;
+        let attr_type = u16::from_ne_bytes([data[2], data[3]]) & NLA_TYPE_MASK;
+        if len < NLA_HDRLEN || len > data.len() {
+            break;
+        }
+        attrs.push((attr_type, &data[NLA_HDRLEN..len]));
+        data = &data[align(len).min(data.len())..];
+    }
+    attrs
+}
+
+/// Netlink socket that is closed when dropped
+struct Socket(i32);
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        // SAFETY: the file descriptor is owned by this struct
+        unsafe { libc::close(self.0) };
+    }
+}
+
+impl Socket {
+    fn open() -> io::Result<Self> {
+        // SAFETY: plain system calls, the timeout struct outlives the call
+        unsafe {
+            let fd = libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_GENERIC,
+            );
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let socket = Self(fd);
+
+            // Don't hang the whole program if the kernel doesn't reply
+            let timeout = libc::timeval {
+                tv_sec: 1,
+                tv_usec: 0,
+            };
+            libc::setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &timeout as *const _ as *const libc::c_void,
+                mem::size_of::<libc::timeval>() as libc::socklen_t,
+            );
+            Ok(socket)
+        }
+    }
+
+    /// Sends a request to the kernel and returns the attributes of the reply
+    fn request(&self, family: u16, command: u8, attrs: &[(u16, &[u8])]) -> io::Result<Vec<u8>> {
+        let msg = build_request(family, command, 1, attrs);
+        let mut buf = vec![0u8; 8192];
+
+        // SAFETY: the buffers are valid for the given lengths and the address
+        // struct is zeroed before use
+        let received = unsafe {
+            let mut addr: libc::sockaddr_nl = mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            let sent = libc::sendto(
+                self.0,
+                msg.as_ptr() as *const libc::c_void,
+                msg.len(),
+                0,
+                &addr as *const _ as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            );
+            if sent < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            libc::recv(self.0, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0)
+        };
+        if received < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let reply = &buf[..received as usize];
+        if reply.len() < NLMSG_HDRLEN + GENL_HDRLEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Reply too short",
+            ));
+        }
+
+        let len = (u32::from_ne_bytes([reply[0], reply[1], reply[2], reply[3]]) as usize)
+            .min(reply.len());
+        let msg_type = u16::from_ne_bytes([reply[4], reply[5]]);
+        if msg_type == NLMSG_ERROR {
+            let code = i32::from_ne_bytes([reply[16], reply[17], reply[18], reply[19]]);
+            return Err(io::Error::from_raw_os_error(-code));
+        }
+        Ok(reply[NLMSG_HDRLEN + GENL_HDRLEN..len].to_vec())
+    }
+}
+
+/// Returns the SSID of the network a wireless interface is connected to. The
+/// SSID is only available through nl80211, which is queried with a minimal
+/// generic netlink client instead of depending on a netlink library.
+pub fn get_ssid(interface: &str) -> Option<String> {
+    let name = CString::new(interface).ok()?;
+    // SAFETY: name is a valid null terminated string
+    let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+    if index == 0 {
+        return None;
+    }
+
+    let socket = Socket::open().ok()?;
+    let reply = socket
+        .request(
+            GENL_ID_CTRL,
+            CTRL_CMD_GETFAMILY,
+            &[(CTRL_ATTR_FAMILY_NAME, b"nl80211\0")],
+        )
+        .ok()?;
+    let family = parse_attrs(&reply)
+        .into_iter()
+        .find(|(attr_type, payload)| *attr_type == CTRL_ATTR_FAMILY_ID && payload.len() >= 2)
+        .map(|(_, payload)| u16::from_ne_bytes([payload[0], payload[1]]))?;
+
+    let reply = socket
+        .request(
+            family,
+            NL80211_CMD_GET_INTERFACE,
+            &[(NL80211_ATTR_IFINDEX, &index.to_ne_bytes())],
+        )
+        .ok()?;
+    parse_attrs(&reply)
+        .into_iter()
+        .find(|(attr_type, _)| *attr_type == NL80211_ATTR_SSID)
+        .map(|(_, ssid)| String::from_utf8_lossy(ssid).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_request_attrs_roundtrip() {
+        let msg = build_request(
+            0x1C,
+            NL80211_CMD_GET_INTERFACE,
+            1,
+            &[
+                (NL80211_ATTR_IFINDEX, &3u32.to_ne_bytes()),
+                (NL80211_ATTR_SSID, b"home"),
+                (CTRL_ATTR_FAMILY_NAME, b"nl80211\0"),
+            ],
+        );
+        assert_eq!(msg.len() % 4, 0);
+        assert_eq!(
+            u32::from_ne_bytes([msg[0], msg[1], msg[2], msg[3]]) as usize,
+            msg.len()
+        );
+
+        let attrs = parse_attrs(&msg[NLMSG_HDRLEN + GENL_HDRLEN..]);
+        assert_eq!(attrs.len(), 3);
+        assert_eq!(attrs[0], (NL80211_ATTR_IFINDEX, &3u32.to_ne_bytes()[..]));
+        assert_eq!(attrs[1], (NL80211_ATTR_SSID, &b"home"[..]));
+        assert_eq!(attrs[2], (CTRL_ATTR_FAMILY_NAME, &b"nl80211\0"[..]));
+    }
+}
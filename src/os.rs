@@ -1,12 +1,23 @@
 use std::collections::HashMap;
-use std::env::consts;
+use std::env::{self, consts};
 use std::fs;
 
+use serde::{Deserialize, Serialize};
+
 use crate::config::Config;
 use crate::Module;
 
 const OS_RELEASE_PATH: &str = "/etc/os-release";
 const OS_KERNEL_PATH: &str = "/proc/sys/kernel/osrelease";
+/// Full kernel version string, including the compiler and build date
+const PROC_VERSION_PATH: &str = "/proc/version";
+/// Build number, flags and date of the kernel
+const KERNEL_VERSION_PATH: &str = "/proc/sys/kernel/version";
+/// Bitmask of the reasons the kernel is tainted, 0 when it isn't
+const KERNEL_TAINTED_PATH: &str = "/proc/sys/kernel/tainted";
+const CMDLINE_PATH: &str = "/proc/cmdline";
+/// One line per loaded kernel module
+const MODULES_PATH: &str = "/proc/modules";
 
 pub struct Distro {
     header: String,
@@ -86,9 +97,151 @@ impl Module for Architechture {
     }
 }
 
+/// Details of the Windows Subsystem for Linux, when running in it
+#[derive(Debug, PartialEq)]
+struct Wsl {
+    /// 1 for the translation layer, 2 for the Hyper-V VM
+    version: u8,
+    windows_build: Option<String>,
+    distro: Option<String>,
+}
+
+/// Detects WSL from the kernel release, which is built by Microsoft. WSL 1
+/// reports a fake kernel like "4.4.0-19041-Microsoft", with the Windows build
+/// as part of the version. WSL 2 runs a real kernel named like
+/// "5.15.133.1-microsoft-standard-WSL2", which doesn't contain the build.
+fn parse_wsl(release: &str, proc_version: &str, distro: Option<String>) -> Option<Wsl> {
+    if !release.to_lowercase().contains("microsoft") && !release.contains("WSL") {
+        return None;
+    }
+    let version = if release.contains("WSL2") || release.contains("microsoft") {
+        2
+    } else {
+        1
+    };
+    let windows_build = match version {
+        1 => proc_version
+            .split_whitespace()
+            .nth(2)
+            .and_then(|kernel| kernel.split('-').nth(1))
+            .filter(|build| !build.is_empty() && build.chars().all(|c| c.is_ascii_digit()))
+            .map(String::from),
+        _ => None,
+    };
+    Some(Wsl {
+        version,
+        windows_build,
+        distro: distro.filter(|d| !d.is_empty()),
+    })
+}
+
+/// Build details of the running kernel, like:
+/// #1 SMP PREEMPT_DYNAMIC Debian 6.1.55-1 (2023-09-29)
+#[derive(Debug, PartialEq)]
+struct KernelBuild {
+    /// Preemption model, like PREEMPT, PREEMPT_DYNAMIC or PREEMPT_RT
+    preempt: Option<String>,
+    date: Option<String>,
+}
+
+fn parse_kernel_version(version: &str) -> KernelBuild {
+    let words = version
+        .split_whitespace()
+        .skip_while(|word| word.starts_with('#'))
+        .collect::<Vec<_>>();
+    let preempt = words
+        .iter()
+        .find(|word| word.starts_with("PREEMPT"))
+        .map(|word| word.to_string());
+    let rest = words
+        .iter()
+        .skip_while(|word| **word == "SMP" || word.starts_with("PREEMPT"))
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+    // Distribution kernels put the date in parentheses after their own version
+    let date = match (rest.rfind('('), rest.ends_with(')')) {
+        (Some(open), true) => rest[open + 1..rest.len() - 1].to_string(),
+        _ => rest,
+    };
+    KernelBuild {
+        preempt,
+        date: if date.is_empty() { None } else { Some(date) },
+    }
+}
+
+/// Letters for the bits of /proc/sys/kernel/tainted, as printed in kernel
+/// oops messages. See Documentation/admin-guide/tainted-kernels.rst.
+const TAINT_FLAGS: &[char] = &[
+    'P', 'F', 'S', 'R', 'M', 'B', 'U', 'D', 'A', 'W', 'C', 'I', 'O', 'E', 'L', 'K', 'X', 'T', 'N',
+    'J',
+];
+
+/// Decodes the taint bitmask into flag letters, like "PO" for a kernel with a
+/// proprietary out of tree module loaded
+fn decode_tainted(tainted: u64) -> String {
+    TAINT_FLAGS
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| tainted & (1 << bit) != 0)
+        .map(|(_, flag)| *flag)
+        .collect()
+}
+
+/// Replaces the values of parameters containing one of the redacted words,
+/// like cryptkey=... or rd.luks.key=...
+fn redact_cmdline(cmdline: &str, redacted: &[String]) -> String {
+    cmdline
+        .split_whitespace()
+        .map(|param| match param.split_once('=') {
+            Some((key, _))
+                if redacted
+                    .iter()
+                    .any(|word| key.to_lowercase().contains(&word.to_lowercase())) =>
+            {
+                format!("{}=<redacted>", key)
+            }
+            _ => param.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Kernel {
     header: String,
+    #[serde(skip)]
     version: Option<String>,
+    #[serde(skip)]
+    wsl: Option<Wsl>,
+    #[serde(skip)]
+    build: Option<KernelBuild>,
+    #[serde(skip)]
+    tainted: Option<u64>,
+    #[serde(skip)]
+    cmdline: Option<String>,
+    #[serde(skip)]
+    module_count: Option<usize>,
+
+    /// Whether to add the build date of the kernel
+    show_build_date: bool,
+    /// Whether to add the preemption model of the kernel
+    show_preempt: bool,
+    /// Whether to add the number of loaded kernel modules
+    show_module_count: bool,
+
+    tainted_header: String,
+    /// Whether to show the taint flags on a separate line when the kernel is
+    /// tainted
+    show_tainted: bool,
+
+    cmdline_header: String,
+    /// Whether to show the kernel command line on a separate line
+    show_cmdline: bool,
+    /// Parameters whose names contain one of these words have their values
+    /// hidden
+    cmdline_redacted: Vec<String>,
 }
 
 impl Default for Kernel {
@@ -101,9 +254,38 @@ impl Default for Kernel {
             }
         };
 
+        let wsl = kernel.as_deref().and_then(|release| {
+            let proc_version = fs::read_to_string(PROC_VERSION_PATH).unwrap_or_default();
+            parse_wsl(release, &proc_version, env::var("WSL_DISTRO_NAME").ok())
+        });
+
         Self {
             header: String::from("Kernel"),
             version: kernel,
+            wsl,
+            build: fs::read_to_string(KERNEL_VERSION_PATH)
+                .ok()
+                .map(|v| parse_kernel_version(&v)),
+            tainted: fs::read_to_string(KERNEL_TAINTED_PATH)
+                .ok()
+                .and_then(|t| t.trim().parse().ok()),
+            cmdline: fs::read_to_string(CMDLINE_PATH)
+                .ok()
+                .map(|c| c.trim().to_string()),
+            module_count: fs::read_to_string(MODULES_PATH)
+                .ok()
+                .map(|m| m.lines().count()),
+            show_build_date: false,
+            show_preempt: false,
+            show_module_count: false,
+            tainted_header: String::from("Tainted"),
+            show_tainted: false,
+            cmdline_header: String::from("Cmdline"),
+            show_cmdline: false,
+            cmdline_redacted: ["password", "passwd", "secret", "token", "key"]
+                .iter()
+                .map(|w| w.to_string())
+                .collect(),
         }
     }
 }
@@ -111,7 +293,55 @@ impl Default for Kernel {
 impl Module for Kernel {
     fn print(&self, config: &Config) {
         if let Some(v) = &self.version {
-            println!("{}: {}", config.color.bold().paint(&self.header), v);
+            let mut kernel = v.clone();
+            let mut details = Vec::new();
+            if let Some(wsl) = &self.wsl {
+                details.push(format!("WSL {}", wsl.version));
+                if let Some(build) = &wsl.windows_build {
+                    details.push(format!("Windows build {}", build));
+                }
+                if let Some(distro) = &wsl.distro {
+                    details.push(distro.clone());
+                }
+            }
+            if let Some(build) = &self.build {
+                if let (true, Some(preempt)) = (config.kernel.show_preempt, &build.preempt) {
+                    details.push(preempt.clone());
+                }
+                if let (true, Some(date)) = (config.kernel.show_build_date, &build.date) {
+                    details.push(format!("built {}", date));
+                }
+            }
+            if let (true, Some(count)) = (config.kernel.show_module_count, self.module_count) {
+                details.push(format!("{} modules", count));
+            }
+            if !details.is_empty() {
+                kernel.push_str(&format!(" ({})", details.join(", ")));
+            }
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.kernel.header),
+                kernel
+            );
+        }
+
+        if let (true, Some(tainted)) = (config.kernel.show_tainted, self.tainted) {
+            if tainted != 0 {
+                println!(
+                    "{}: {} ({})",
+                    config.color.bold().paint(&config.kernel.tainted_header),
+                    decode_tainted(tainted),
+                    tainted
+                );
+            }
+        }
+
+        if let (true, Some(cmdline)) = (config.kernel.show_cmdline, &self.cmdline) {
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.kernel.cmdline_header),
+                redact_cmdline(cmdline, &config.kernel.cmdline_redacted)
+            );
         }
     }
 }
@@ -136,4 +366,70 @@ LOGO=archlinux
         assert_eq!(release_map.get("NAME"), Some(&"Arch Linux"));
         assert_eq!(release_map.get("BUILD_ID"), Some(&"rolling"));
     }
+
+    #[test]
+    fn test_parse_wsl() {
+        assert_eq!(
+            parse_wsl(
+                "5.15.133.1-microsoft-standard-WSL2",
+                "Linux version 5.15.133.1-microsoft-standard-WSL2 (root@1c602f52c2e4) #1 SMP",
+                Some(String::from("Ubuntu-22.04")),
+            ),
+            Some(Wsl {
+                version: 2,
+                windows_build: None,
+                distro: Some(String::from("Ubuntu-22.04")),
+            })
+        );
+        assert_eq!(
+            parse_wsl(
+                "4.4.0-19041-Microsoft",
+                "Linux version 4.4.0-19041-Microsoft (Microsoft@Microsoft.com) #1237-Microsoft",
+                None,
+            ),
+            Some(Wsl {
+                version: 1,
+                windows_build: Some(String::from("19041")),
+                distro: None,
+            })
+        );
+        assert_eq!(parse_wsl("6.1.0-13-amd64", "", None), None);
+    }
+
+    #[test]
+    fn test_parse_kernel_version() {
+        assert_eq!(
+            parse_kernel_version("#1 SMP PREEMPT_DYNAMIC Debian 6.1.55-1 (2023-09-29)\n"),
+            KernelBuild {
+                preempt: Some(String::from("PREEMPT_DYNAMIC")),
+                date: Some(String::from("2023-09-29")),
+            }
+        );
+        assert_eq!(
+            parse_kernel_version("#1 SMP Thu Oct 5 21:02:42 UTC 2023\n"),
+            KernelBuild {
+                preempt: None,
+                date: Some(String::from("Thu Oct 5 21:02:42 UTC 2023")),
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_tainted() {
+        assert_eq!(decode_tainted(0), "");
+        // Proprietary, out of tree and unsigned module
+        assert_eq!(decode_tainted(12289), "POE");
+    }
+
+    #[test]
+    fn test_redact_cmdline() {
+        let redacted = vec![String::from("key"), String::from("password")];
+        assert_eq!(
+            redact_cmdline(
+                "BOOT_IMAGE=/vmlinuz root=UUID=1234 rd.luks.key=/key.bin quiet splash",
+                &redacted
+            ),
+            "BOOT_IMAGE=/vmlinuz root=UUID=1234 rd.luks.key=<redacted> quiet splash"
+        );
+    }
 }
@@ -1,36 +1,530 @@
 use serde::{Deserialize, Serialize};
 
+use std::collections::HashSet;
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::Config;
 use crate::Module;
 
 const PACKAGE_MANAGERS: &[PackageManager] = &[
-    PackageManager::new("pacman", &["--query", "--quiet"]),
-    PackageManager::new("dpkg", &["--get-selections"]),
+    PackageManager::new("pacman", &["--query", "--quiet"]).with_native(count_pacman),
+    PackageManager::new("dpkg", &["--get-selections"]).with_native(count_dpkg),
     PackageManager::new("rpm", &["--query", "--all"]),
-    PackageManager::new("apk", &["info"]),
-    PackageManager::new("xbps-query", &["--list-pkgs"]),
-    PackageManager::new("flatpak", &["list"]),
+    PackageManager::new("apk", &["info"]).with_native(count_apk),
+    PackageManager::new("xbps-query", &["--list-pkgs"]).with_native(count_xbps),
+    PackageManager::native("flatpak", count_flatpak),
+    PackageManager::native("nix-default", count_nix_default),
+    PackageManager::native("nix-user", count_nix_user),
+    PackageManager::native("guix-system", count_guix_system),
+    PackageManager::native("guix-user", count_guix_user),
+    PackageManager::native("snap", count_snap),
+    PackageManager::native("brew", count_brew),
+    PackageManager::native("emerge", count_portage),
+    PackageManager::native("pkgtool", count_slackware),
+    PackageManager::new("eopkg", &["list-installed"]).with_native(count_eopkg),
+    PackageManager::new("pkg_info", &[]).with_native(count_pkgsrc),
+    PackageManager::native("appimage", count_appimages),
 ];
 
+/// Databases of the package managers, relative to the root directory
+const PACMAN_LOCAL_PATH: &str = "var/lib/pacman/local";
+const APK_INSTALLED_PATH: &str = "lib/apk/db/installed";
+const XBPS_PKGDB_PATH: &str = "var/db/xbps/pkgdb-0.38.plist";
+const DPKG_STATUS_PATH: &str = "var/lib/dpkg/status";
+/// Records which packages apt installed automatically as dependencies
+const APT_EXTENDED_STATES_PATH: &str = "var/lib/apt/extended_states";
+/// Profile of packages installed for all users, like with sudo nix-env
+const NIX_DEFAULT_PROFILE_PATH: &str = "nix/var/nix/profiles/default";
+/// Default user profile of older Nix versions, newer ones link ~/.nix-profile
+/// to the per-user profile in the state directory
+const NIX_USER_PROFILE_PATH: &str = ".nix-profile";
+const GUIX_SYSTEM_PROFILE_PATH: &str = "run/current-system/profile";
+const GUIX_USER_PROFILE_PATH: &str = ".guix-profile";
+/// Mount points of snaps, with a directory per snap and a bin directory.
+/// Distributions that don't allow /snap use /var/lib/snapd/snap.
+const SNAP_PATHS: &[&str] = &["snap", "var/lib/snapd/snap"];
+/// Cellar with a directory per formula, for Homebrew installed system wide
+/// and in the home directory
+const BREW_SYSTEM_CELLAR_PATH: &str = "home/linuxbrew/.linuxbrew/Cellar";
+const BREW_USER_CELLAR_PATH: &str = ".linuxbrew/Cellar";
+/// Portage database with a directory per category and package, like
+/// sys-apps/portage-3.0.57
+const PORTAGE_DB_PATH: &str = "var/db/pkg";
+/// Slackware has a file per installed package
+const SLACKWARE_PACKAGES_PATH: &str = "var/log/packages";
+const EOPKG_PACKAGES_PATH: &str = "var/lib/eopkg/package";
+const PKGSRC_DB_PATH: &str = "usr/pkg/pkgdb";
+const APPIMAGES_PATH: &str = "Applications";
+/// Flatpak installations, with app and runtime directories containing a
+/// directory per installed ref like org.gnome.Platform/x86_64/45
+const FLATPAK_SYSTEM_PATH: &str = "var/lib/flatpak";
+const FLATPAK_USER_PATH: &str = ".local/share/flatpak";
+
+/// Directories the package databases are looked up in
+struct Roots {
+    /// Root of the file system, for system wide package managers
+    system: PathBuf,
+    /// Home directory, for per-user package managers
+    home: Option<PathBuf>,
+}
+
+impl Default for Roots {
+    fn default() -> Self {
+        Self {
+            system: PathBuf::from("/"),
+            home: dirs::home_dir(),
+        }
+    }
+}
+
+/// Counts the packages installed automatically as dependencies. It is only
+/// run when manual counts are shown.
+type AutomaticCount = fn(&Roots) -> Option<u32>;
+
+/// Package count read from the database of a package manager
+struct NativeCount {
+    installed: u32,
+    /// For package managers that record which packages were installed
+    /// automatically as dependencies
+    automatic: Option<AutomaticCount>,
+    /// Breakdown of the flatpak count, which can be split by config
+    flatpak: Option<FlatpakCounts>,
+}
+
+impl NativeCount {
+    fn new(installed: u32) -> Self {
+        Self {
+            installed,
+            automatic: None,
+            flatpak: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct FlatpakCounts {
+    system_apps: u32,
+    system_runtimes: u32,
+    user_apps: u32,
+    user_runtimes: u32,
+}
+
+/// Counts the subdirectories of a directory
+fn count_dirs(path: &Path) -> Option<u32> {
+    let entries = read_dir(path).ok()?;
+    let count = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .count();
+    Some(count as u32)
+}
+
+/// pacman has a directory per installed package, like zstd-1.5.5-1
+fn count_pacman(roots: &Roots) -> Option<NativeCount> {
+    count_dirs(&roots.system.join(PACMAN_LOCAL_PATH)).map(NativeCount::new)
+}
+
+/// The apk database has a block per package, starting with its name like:
+/// P:musl
+fn count_apk(roots: &Roots) -> Option<NativeCount> {
+    let installed = read_to_string(roots.system.join(APK_INSTALLED_PATH)).ok()?;
+    let count = installed.lines().filter(|l| l.starts_with("P:")).count();
+    Some(NativeCount::new(count as u32))
+}
+
+/// The xbps database is a property list with a dictionary per package, each
+/// with a pkgver key
+fn count_xbps(roots: &Roots) -> Option<NativeCount> {
+    let pkgdb = read_to_string(roots.system.join(XBPS_PKGDB_PATH)).ok()?;
+    Some(NativeCount::new(
+        pkgdb.matches("<key>pkgver</key>").count() as u32
+    ))
+}
+
+/// Parses the stanzas of a Debian control file like dpkg's status, which are
+/// separated by blank lines, into lists of fields. Continuation lines of
+/// multiline fields are skipped.
+fn parse_stanzas(contents: &str) -> Vec<Vec<(&str, &str)>> {
+    contents
+        .split("\n\n")
+        .map(|stanza| {
+            stanza
+                .lines()
+                .filter(|line| !line.starts_with([' ', '\t']))
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key, value.trim()))
+                .collect::<Vec<_>>()
+        })
+        .filter(|fields| !fields.is_empty())
+        .collect()
+}
+
+fn field<'a>(fields: &[(&str, &'a str)], name: &str) -> Option<&'a str> {
+    fields
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| *value)
+}
+
+/// Returns the names and architectures of the installed packages in dpkg's
+/// status file. Unlike dpkg --get-selections, packages that are removed but
+/// still have configuration files are left out. Held packages, with a status
+/// of "hold ok installed", are installed as well.
+fn parse_dpkg_status(status: &str) -> Vec<(&str, &str)> {
+    parse_stanzas(status)
+        .iter()
+        .filter(|fields| {
+            matches!(
+                field(fields, "Status"),
+                Some("install ok installed") | Some("hold ok installed")
+            )
+        })
+        .filter_map(|fields| {
+            Some((
+                field(fields, "Package")?,
+                field(fields, "Architecture").unwrap_or("all"),
+            ))
+        })
+        .collect()
+}
+
+/// Counts the installed packages that apt marked as automatically installed
+fn count_automatic(installed: &[(&str, &str)], extended_states: &str) -> u32 {
+    let stanzas = parse_stanzas(extended_states);
+    let automatic = stanzas
+        .iter()
+        .filter(|fields| field(fields, "Auto-Installed") == Some("1"))
+        .filter_map(|fields| Some((field(fields, "Package")?, field(fields, "Architecture"))))
+        .collect::<HashSet<_>>();
+    let names = automatic
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<HashSet<_>>();
+    installed
+        .iter()
+        .filter(|(name, arch)| {
+            // apt records architecture independent packages with the native
+            // architecture
+            match *arch {
+                "all" => names.contains(name),
+                arch => {
+                    automatic.contains(&(*name, Some(arch))) || automatic.contains(&(*name, None))
+                }
+            }
+        })
+        .count() as u32
+}
+
+fn count_dpkg_automatic(roots: &Roots) -> Option<u32> {
+    let status = read_to_string(roots.system.join(DPKG_STATUS_PATH)).ok()?;
+    let extended_states = read_to_string(roots.system.join(APT_EXTENDED_STATES_PATH)).ok()?;
+    Some(count_automatic(
+        &parse_dpkg_status(&status),
+        &extended_states,
+    ))
+}
+
+fn count_dpkg(roots: &Roots) -> Option<NativeCount> {
+    let status = read_to_string(roots.system.join(DPKG_STATUS_PATH)).ok()?;
+    Some(NativeCount {
+        installed: parse_dpkg_status(&status).len() as u32,
+        automatic: Some(count_dpkg_automatic),
+        flatpak: None,
+    })
+}
+
+/// Counts the packages in the manifest of a Nix profile. Profiles managed by
+/// nix-env have a manifest.nix with an attribute set per package, each with
+/// type = "derivation". Profiles managed by nix profile have a manifest.json
+/// with an element per package, each with a list of storePaths. The closure
+/// of the NixOS system profile is only recorded in the Nix database, so it
+/// isn't counted.
+fn count_nix_profile(profile: &Path) -> Option<NativeCount> {
+    if let Ok(manifest) = read_to_string(profile.join("manifest.nix")) {
+        let count = manifest.matches("type = \"derivation\"").count();
+        return Some(NativeCount::new(count as u32));
+    }
+    let manifest = read_to_string(profile.join("manifest.json")).ok()?;
+    let count = manifest.matches("\"storePaths\"").count();
+    Some(NativeCount::new(count as u32))
+}
+
+fn count_nix_default(roots: &Roots) -> Option<NativeCount> {
+    count_nix_profile(&roots.system.join(NIX_DEFAULT_PROFILE_PATH))
+}
+
+fn count_nix_user(roots: &Roots) -> Option<NativeCount> {
+    count_nix_profile(&roots.home.as_ref()?.join(NIX_USER_PROFILE_PATH))
+}
+
+/// Counts the packages in a Guix manifest, an S-expression like:
+/// (manifest (version 4) (packages (("coreutils" "9.1" "out" "/gnu/store/..."
+/// (propagated-inputs ()) ...) ...))). Propagated inputs are nested in the
+/// packages and aren't counted, like guix package --list-installed.
+fn count_guix_manifest(manifest: &str) -> Option<u32> {
+    let packages = &manifest[manifest.find("(packages")? + "(packages".len()..];
+    let mut depth = 0;
+    let mut count = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in packages.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => (),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' => {
+                depth += 1;
+                // The packages are in a list inside the packages field
+                if depth == 2 {
+                    count += 1;
+                }
+            }
+            ')' if depth == 0 => break,
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+    Some(count)
+}
+
+fn count_guix_profile(profile: &Path) -> Option<NativeCount> {
+    let manifest = read_to_string(profile.join("manifest")).ok()?;
+    count_guix_manifest(&manifest).map(NativeCount::new)
+}
+
+fn count_guix_system(roots: &Roots) -> Option<NativeCount> {
+    count_guix_profile(&roots.system.join(GUIX_SYSTEM_PROFILE_PATH))
+}
+
+fn count_guix_user(roots: &Roots) -> Option<NativeCount> {
+    count_guix_profile(&roots.home.as_ref()?.join(GUIX_USER_PROFILE_PATH))
+}
+
+fn count_snap(roots: &Roots) -> Option<NativeCount> {
+    let (path, snaps) = SNAP_PATHS.iter().find_map(|path| {
+        let path = roots.system.join(path);
+        let snaps = count_dirs(&path)?;
+        Some((path, snaps))
+    })?;
+    let has_bin = path.join("bin").is_dir();
+    match snaps.saturating_sub(has_bin as u32) {
+        0 => None,
+        count => Some(NativeCount::new(count)),
+    }
+}
+
+fn count_brew(roots: &Roots) -> Option<NativeCount> {
+    let system = count_dirs(&roots.system.join(BREW_SYSTEM_CELLAR_PATH));
+    let user = roots
+        .home
+        .as_ref()
+        .and_then(|home| count_dirs(&home.join(BREW_USER_CELLAR_PATH)));
+    match (system, user) {
+        (None, None) => None,
+        (system, user) => Some(NativeCount::new(system.unwrap_or(0) + user.unwrap_or(0))),
+    }
+}
+
+fn count_portage(roots: &Roots) -> Option<NativeCount> {
+    let categories = read_dir(roots.system.join(PORTAGE_DB_PATH)).ok()?;
+    let count = categories
+        .filter_map(|entry| entry.ok())
+        .filter_map(|category| count_dirs(&category.path()))
+        .sum();
+    Some(NativeCount::new(count))
+}
+
+fn count_slackware(roots: &Roots) -> Option<NativeCount> {
+    let packages = read_dir(roots.system.join(SLACKWARE_PACKAGES_PATH)).ok()?;
+    Some(NativeCount::new(packages.count() as u32))
+}
+
+fn count_eopkg(roots: &Roots) -> Option<NativeCount> {
+    count_dirs(&roots.system.join(EOPKG_PACKAGES_PATH)).map(NativeCount::new)
+}
+
+fn count_pkgsrc(roots: &Roots) -> Option<NativeCount> {
+    count_dirs(&roots.system.join(PKGSRC_DB_PATH)).map(NativeCount::new)
+}
+
+fn count_appimages(roots: &Roots) -> Option<NativeCount> {
+    let entries = read_dir(roots.home.as_ref()?.join(APPIMAGES_PATH)).ok()?;
+    let count = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .to_lowercase()
+                .ends_with(".appimage")
+        })
+        .count();
+    match count {
+        0 => None,
+        count => Some(NativeCount::new(count as u32)),
+    }
+}
+
+/// Counts the refs in a flatpak app or runtime directory. Like flatpak list,
+/// each architecture and branch of an app or runtime is counted.
+fn count_flatpak_refs(path: &Path) -> Option<u32> {
+    let ids = read_dir(path).ok()?;
+    let count = ids
+        .filter_map(|entry| entry.ok())
+        .filter_map(|id| read_dir(id.path()).ok())
+        .flat_map(|arches| arches.filter_map(|entry| entry.ok()))
+        // Skips the current symlink next to the architectures
+        .filter(|arch| arch.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|arch| count_dirs(&arch.path()))
+        .sum();
+    Some(count)
+}
+
+/// Counts the refs in the flatpak installations, None when neither exists
+fn read_flatpak_dirs(roots: &Roots) -> Option<FlatpakCounts> {
+    let system = roots.system.join(FLATPAK_SYSTEM_PATH);
+    let user = roots.home.as_ref().map(|home| home.join(FLATPAK_USER_PATH));
+    let count = |installation: Option<&PathBuf>, dir: &str| {
+        installation.and_then(|path| count_flatpak_refs(&path.join(dir)))
+    };
+    let counts = [
+        count(Some(&system), "app"),
+        count(Some(&system), "runtime"),
+        count(user.as_ref(), "app"),
+        count(user.as_ref(), "runtime"),
+    ];
+    if counts.iter().all(Option::is_none) {
+        return None;
+    }
+    let [system_apps, system_runtimes, user_apps, user_runtimes] = counts.map(|c| c.unwrap_or(0));
+    Some(FlatpakCounts {
+        system_apps,
+        system_runtimes,
+        user_apps,
+        user_runtimes,
+    })
+}
+
+/// Counts the system wide and per-user refs in the output of
+/// flatpak list --columns=installation, which has a line per ref with the name
+/// of its installation. Installations other than the user one, like those in
+/// /etc/flatpak/installations.d, are system wide.
+fn count_installations(output: &str) -> (u32, u32) {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|installation| !installation.is_empty())
+        .fold((0, 0), |(system, user), installation| {
+            if installation == "user" {
+                (system, user + 1)
+            } else {
+                (system + 1, user)
+            }
+        })
+}
+
+/// Parses the output of flatpak list --app and --runtime
+fn parse_flatpak_list(apps: &str, runtimes: &str) -> FlatpakCounts {
+    let (system_apps, user_apps) = count_installations(apps);
+    let (system_runtimes, user_runtimes) = count_installations(runtimes);
+    FlatpakCounts {
+        system_apps,
+        system_runtimes,
+        user_apps,
+        user_runtimes,
+    }
+}
+
+/// Lists the refs of a kind, --app or --runtime, with their installations
+fn run_flatpak_list(kind: &str) -> Option<String> {
+    let output = Command::new("flatpak")
+        .args(["list", kind, "--columns=installation"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Counts the refs by running flatpak, for installations in other locations
+fn list_flatpak() -> Option<FlatpakCounts> {
+    let apps = run_flatpak_list("--app")?;
+    let runtimes = run_flatpak_list("--runtime")?;
+    Some(parse_flatpak_list(&apps, &runtimes))
+}
+
+impl From<FlatpakCounts> for NativeCount {
+    fn from(flatpak: FlatpakCounts) -> Self {
+        Self {
+            installed: flatpak.system_apps
+                + flatpak.system_runtimes
+                + flatpak.user_apps
+                + flatpak.user_runtimes,
+            automatic: None,
+            flatpak: Some(flatpak),
+        }
+    }
+}
+
+fn count_flatpak(roots: &Roots) -> Option<NativeCount> {
+    read_flatpak_dirs(roots)
+        .or_else(list_flatpak)
+        .map(NativeCount::from)
+}
+
 struct PackageManager<'a> {
     name: &'a str,
-    /// Arguments to list installed packages
-    args: &'a [&'a str],
+    /// Arguments to list installed packages, None for package managers that
+    /// are only counted natively
+    args: Option<&'a [&'a str]>,
+    /// Counts the packages by reading the database of the package manager,
+    /// which is much faster than running it. Returns None when the database
+    /// doesn't exist.
+    native: Option<fn(&Roots) -> Option<NativeCount>>,
 }
 
 impl<'a> PackageManager<'a> {
     const fn new(name: &'a str, args: &'a [&'a str]) -> Self {
-        Self { name, args }
+        Self {
+            name,
+            args: Some(args),
+            native: None,
+        }
+    }
+
+    const fn native(name: &'a str, native: fn(&Roots) -> Option<NativeCount>) -> Self {
+        Self {
+            name,
+            args: None,
+            native: Some(native),
+        }
+    }
+
+    const fn with_native(self, native: fn(&Roots) -> Option<NativeCount>) -> Self {
+        Self {
+            native: Some(native),
+            ..self
+        }
     }
 
     fn is_installed(&self) -> bool {
-        Command::new(self.name).output().is_ok()
+        self.args.is_some() && Command::new(self.name).output().is_ok()
     }
 
     fn get_package_count(&self) -> Option<u32> {
-        let command_output = Command::new(self.name).args(self.args).output();
+        let command_output = Command::new(self.name)
+            .args(self.args.unwrap_or_default())
+            .output();
         let package_list = match command_output {
             Ok(output) => output.stdout,
             Err(_) => return None,
@@ -42,9 +536,62 @@ impl<'a> PackageManager<'a> {
 
 struct PackageCount {
     count: u32,
+    automatic: Option<AutomaticCount>,
+    flatpak: Option<FlatpakCounts>,
     package_manager: String,
 }
 
+impl PackageCount {
+    fn new(package_manager: &str, native_count: NativeCount) -> Self {
+        Self {
+            count: native_count.installed,
+            automatic: native_count.automatic,
+            flatpak: native_count.flatpak,
+            package_manager: package_manager.to_string(),
+        }
+    }
+
+    /// Returns the counts to show and their labels. Flatpak is split into
+    /// system and user installations when configured.
+    fn entries(&self, config: &Packages, roots: &Roots) -> Vec<(u32, String)> {
+        if let Some(flatpak) = &self.flatpak {
+            let count = |apps: u32, runtimes: u32| {
+                if config.flatpak_apps_only {
+                    apps
+                } else {
+                    apps + runtimes
+                }
+            };
+            let system = count(flatpak.system_apps, flatpak.system_runtimes);
+            let user = count(flatpak.user_apps, flatpak.user_runtimes);
+            // Installations without packages are left out, unless both are
+            // empty
+            if config.split_flatpak && system + user > 0 {
+                return [(system, "flatpak-system"), (user, "flatpak-user")]
+                    .iter()
+                    .filter(|(count, _)| *count > 0)
+                    .map(|(count, label)| (*count, label.to_string()))
+                    .collect();
+            }
+            return vec![(system + user, self.package_manager.clone())];
+        }
+
+        let automatic = match (config.show_manual, self.automatic) {
+            (true, Some(count_automatic)) => count_automatic(roots),
+            _ => None,
+        };
+        let label = match automatic {
+            Some(automatic) => format!(
+                "{}, {} manual",
+                self.package_manager,
+                self.count.saturating_sub(automatic)
+            ),
+            _ => self.package_manager.clone(),
+        };
+        vec![(self.count, label)]
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(default)]
 pub struct Packages {
@@ -54,30 +601,50 @@ pub struct Packages {
     header: String,
     /// Whether to show the package manager names. Format: "count (pkg_manager)"
     show_package_managers: bool,
+    /// Whether to add how many packages were installed manually, for package
+    /// managers that record which ones were installed as dependencies
+    show_manual: bool,
+    /// Whether to count only flatpak applications, leaving out runtimes
+    flatpak_apps_only: bool,
+    /// Whether to count system wide and per-user flatpak installations
+    /// separately
+    split_flatpak: bool,
 }
 
-impl Default for Packages {
-    fn default() -> Self {
-        let mut counts: Vec<PackageCount> = Vec::new();
-        for pkg_manager in PACKAGE_MANAGERS.iter() {
-            if pkg_manager.is_installed() {
-                match pkg_manager.get_package_count() {
-                    Some(count) => counts.push(PackageCount {
-                        count,
-                        package_manager: pkg_manager.name.to_string(),
-                    }),
-                    None => {
-                        eprintln!("Failed to get package count from {}", pkg_manager.name);
-                        continue;
-                    }
+/// Returns the package counts of all package managers, reading their
+/// databases where possible and running them otherwise
+fn get_package_counts(roots: &Roots) -> Vec<PackageCount> {
+    let mut counts: Vec<PackageCount> = Vec::new();
+    for pkg_manager in PACKAGE_MANAGERS.iter() {
+        if let Some(native_count) = pkg_manager.native.and_then(|native| native(roots)) {
+            counts.push(PackageCount::new(pkg_manager.name, native_count));
+        } else if pkg_manager.is_installed() {
+            match pkg_manager.get_package_count() {
+                Some(count) => counts.push(PackageCount {
+                    count,
+                    automatic: None,
+                    flatpak: None,
+                    package_manager: pkg_manager.name.to_string(),
+                }),
+                None => {
+                    eprintln!("Failed to get package count from {}", pkg_manager.name);
+                    continue;
                 }
             }
         }
+    }
+    counts
+}
 
+impl Default for Packages {
+    fn default() -> Self {
         Self {
-            package_counts: counts,
+            package_counts: get_package_counts(&Roots::default()),
             header: String::from("Packages"),
             show_package_managers: true,
+            show_manual: false,
+            flatpak_apps_only: false,
+            split_flatpak: false,
         }
     }
 }
@@ -85,16 +652,22 @@ impl Default for Packages {
 impl Module for Packages {
     fn print(&self, config: &Config) {
         if !self.package_counts.is_empty() {
+            let roots = Roots::default();
+            let entries = self
+                .package_counts
+                .iter()
+                .flat_map(|c| c.entries(&config.packages, &roots))
+                .collect::<Vec<_>>();
             let package_string = if config.packages.show_package_managers {
-                self.package_counts
+                entries
                     .iter()
-                    .map(|c| format!("{} ({})", c.count, c.package_manager))
+                    .map(|(count, label)| format!("{} ({})", count, label))
                     .collect::<Vec<_>>()
                     .join(", ")
             } else {
-                self.package_counts
+                entries
                     .iter()
-                    .fold(0, |acc, pkg_count| acc + pkg_count.count)
+                    .fold(0, |acc, (count, _)| acc + count)
                     .to_string()
             };
 
@@ -106,3 +679,365 @@ impl Module for Packages {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    fn roots(name: &str) -> (Roots, PathBuf) {
+        let root = fixture_dir(name);
+        let roots = Roots {
+            system: root.clone(),
+            home: Some(root.join("home/user")),
+        };
+        (roots, root)
+    }
+
+    /// Builds the config without detecting the package counts of the host
+    fn config() -> Packages {
+        Packages {
+            package_counts: Vec::new(),
+            header: String::from("Packages"),
+            show_package_managers: true,
+            show_manual: false,
+            flatpak_apps_only: false,
+            split_flatpak: false,
+        }
+    }
+
+    #[test]
+    fn test_count_dpkg() {
+        let (roots, root) = roots("packages-dpkg");
+        write_file(
+            &root,
+            DPKG_STATUS_PATH,
+            "Package: bash\nStatus: install ok installed\nArchitecture: amd64\n\n\
+             Package: libc6\nStatus: install ok installed\nArchitecture: amd64\n",
+        );
+        write_file(
+            &root,
+            APT_EXTENDED_STATES_PATH,
+            "Package: libc6\nArchitecture: amd64\nAuto-Installed: 1\n",
+        );
+        let dpkg = PackageCount::new("dpkg", count_dpkg(&roots).unwrap());
+        assert_eq!(dpkg.count, 2);
+
+        let mut config = config();
+        assert_eq!(
+            dpkg.entries(&config, &roots),
+            vec![(2, String::from("dpkg"))]
+        );
+        config.show_manual = true;
+        assert_eq!(
+            dpkg.entries(&config, &roots),
+            vec![(2, String::from("dpkg, 1 manual"))]
+        );
+    }
+
+    #[test]
+    fn test_count_pacman() {
+        let (roots, root) = roots("packages-pacman");
+        assert!(count_pacman(&roots).is_none());
+        write_file(&root, "var/lib/pacman/local/ALPM_DB_VERSION", "9\n");
+        write_file(&root, "var/lib/pacman/local/zstd-1.5.5-1/desc", "");
+        write_file(&root, "var/lib/pacman/local/bash-5.2.015-1/desc", "");
+        assert_eq!(count_pacman(&roots).map(|c| c.installed), Some(2));
+    }
+
+    #[test]
+    fn test_count_apk() {
+        let (roots, root) = roots("packages-apk");
+        assert!(count_apk(&roots).is_none());
+        write_file(
+            &root,
+            APK_INSTALLED_PATH,
+            "C:Q1abc=\nP:musl\nV:1.2.4-r2\n\nC:Q1def=\nP:busybox\nV:1.36.1-r5\n",
+        );
+        assert_eq!(count_apk(&roots).map(|c| c.installed), Some(2));
+    }
+
+    #[test]
+    fn test_count_xbps() {
+        let (roots, root) = roots("packages-xbps");
+        assert!(count_xbps(&roots).is_none());
+        write_file(
+            &root,
+            XBPS_PKGDB_PATH,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<plist version=\"1.0\">
+<dict>
+\t<key>_XBPS_ALTERNATIVES_</key>
+\t<dict>
+\t</dict>
+\t<key>bash</key>
+\t<dict>
+\t\t<key>pkgver</key>
+\t\t<string>bash-5.2.21_1</string>
+\t</dict>
+\t<key>zstd</key>
+\t<dict>
+\t\t<key>automatic-install</key>
+\t\t<true/>
+\t\t<key>pkgver</key>
+\t\t<string>zstd-1.5.5_1</string>
+\t</dict>
+</dict>
+</plist>
+",
+        );
+        assert_eq!(count_xbps(&roots).map(|c| c.installed), Some(2));
+    }
+
+    #[test]
+    fn test_count_nix() {
+        let (roots, root) = roots("packages-nix");
+        assert!(count_nix_default(&roots).is_none());
+        write_file(
+            &root,
+            "nix/var/nix/profiles/default/manifest.nix",
+            "[ { meta = { }; name = \"hello-2.12.1\"; out = { outPath = \"/nix/store/a-hello\"; }; \
+             outputs = [ \"out\" ]; system = \"x86_64-linux\"; type = \"derivation\"; } \
+             { name = \"jq-1.7\"; outputs = [ \"bin\" \"out\" ]; type = \"derivation\"; } ]",
+        );
+        assert_eq!(count_nix_default(&roots).map(|c| c.installed), Some(2));
+
+        write_file(
+            &root,
+            "home/user/.nix-profile/manifest.json",
+            r#"{"elements":{"ripgrep":{"active":true,"storePaths":["/nix/store/b-ripgrep"]}},"version":3}"#,
+        );
+        assert_eq!(count_nix_user(&roots).map(|c| c.installed), Some(1));
+    }
+
+    #[test]
+    fn test_count_guix() {
+        let (roots, root) = roots("packages-guix");
+        assert!(count_guix_user(&roots).is_none());
+        write_file(
+            &root,
+            "home/user/.guix-profile/manifest",
+            r#";; This file was automatically generated
+(manifest
+  (version 4)
+  (packages
+    (("emacs" "29.1" "out" "/gnu/store/a-emacs-29.1"
+      (propagated-inputs
+        (("gcc" "11.3.0" "lib" "/gnu/store/b-gcc-11.3.0-lib"
+          (propagated-inputs ()) (search-paths ()))))
+      (search-paths ())
+      (properties ((comment . ""quoted (parens)""))))
+     ("git" "2.41.0" "out" "/gnu/store/c-git-2.41.0"
+      (propagated-inputs ())
+      (search-paths ())))))
+"#,
+        );
+        assert_eq!(count_guix_user(&roots).map(|c| c.installed), Some(2));
+    }
+
+    #[test]
+    fn test_count_snap() {
+        // Only the bin directory next to the counted snaps is skipped
+        let (roots, root) = roots("packages-snap");
+        write_file(&root, "snap/core22/1033/meta/snap.yaml", "");
+        write_file(&root, "var/lib/snapd/snap/bin/firefox", "");
+        assert_eq!(count_snap(&roots).map(|c| c.installed), Some(1));
+    }
+
+    #[test]
+    fn test_count_brew() {
+        let (roots, root) = roots("packages-brew");
+        assert!(count_brew(&roots).is_none());
+        write_file(
+            &root,
+            "home/linuxbrew/.linuxbrew/Cellar/gcc/13.2.0/bin/gcc",
+            "",
+        );
+        write_file(
+            &root,
+            "home/user/.linuxbrew/Cellar/ripgrep/14.1.0/bin/rg",
+            "",
+        );
+        assert_eq!(count_brew(&roots).map(|c| c.installed), Some(2));
+    }
+
+    #[test]
+    fn test_count_portage() {
+        let (roots, root) = roots("packages-portage");
+        assert!(count_portage(&roots).is_none());
+        write_file(&root, "var/db/pkg/sys-apps/portage-3.0.57/SLOT", "0");
+        write_file(&root, "var/db/pkg/sys-apps/systemd-254.8/SLOT", "0");
+        write_file(&root, "var/db/pkg/dev-lang/rust-1.74.1/SLOT", "stable");
+        assert_eq!(count_portage(&roots).map(|c| c.installed), Some(3));
+    }
+
+    #[test]
+    fn test_count_slackware() {
+        let (roots, root) = roots("packages-slackware");
+        assert!(count_slackware(&roots).is_none());
+        write_file(&root, "var/log/packages/bash-5.2.015-x86_64-1", "");
+        assert_eq!(count_slackware(&roots).map(|c| c.installed), Some(1));
+    }
+
+    #[test]
+    fn test_count_eopkg() {
+        let (roots, root) = roots("packages-eopkg");
+        assert!(count_eopkg(&roots).is_none());
+        write_file(&root, "var/lib/eopkg/package/nano/metadata.xml", "");
+        write_file(
+            &root,
+            "var/lib/eopkg/package/budgie-desktop/metadata.xml",
+            "",
+        );
+        assert_eq!(count_eopkg(&roots).map(|c| c.installed), Some(2));
+    }
+
+    #[test]
+    fn test_count_pkgsrc() {
+        let (roots, root) = roots("packages-pkgsrc");
+        assert!(count_pkgsrc(&roots).is_none());
+        write_file(&root, "usr/pkg/pkgdb/pkgin-23.8.1/+CONTENTS", "");
+        write_file(&root, "usr/pkg/pkgdb/pkg_install-20230417/+CONTENTS", "");
+        write_file(&root, "usr/pkg/pkgdb/pkgdb.byfile.db", "");
+        assert_eq!(count_pkgsrc(&roots).map(|c| c.installed), Some(2));
+    }
+
+    #[test]
+    fn test_count_appimages() {
+        let (roots, root) = roots("packages-appimage");
+        assert!(count_appimages(&roots).is_none());
+        write_file(&root, "home/user/Applications/Obsidian-1.4.16.AppImage", "");
+        write_file(&root, "home/user/Applications/krita.appimage", "");
+        write_file(&root, "home/user/Applications/notes.txt", "");
+        assert_eq!(count_appimages(&roots).map(|c| c.installed), Some(2));
+    }
+
+    #[test]
+    fn test_count_flatpak() {
+        let (roots, root) = roots("packages-flatpak");
+        write_file(&root, "var/lib/flatpak/app/org.gimp.GIMP/current", "");
+        write_file(
+            &root,
+            "var/lib/flatpak/app/org.gimp.GIMP/x86_64/stable/active",
+            "",
+        );
+        write_file(
+            &root,
+            "var/lib/flatpak/runtime/org.gnome.Platform/x86_64/45/active",
+            "",
+        );
+        write_file(
+            &root,
+            "var/lib/flatpak/runtime/org.gnome.Platform/x86_64/46/active",
+            "",
+        );
+        write_file(
+            &root,
+            "home/user/.local/share/flatpak/app/md.obsidian.Obsidian/x86_64/stable/active",
+            "",
+        );
+        assert_eq!(
+            read_flatpak_dirs(&roots),
+            Some(FlatpakCounts {
+                system_apps: 1,
+                system_runtimes: 2,
+                user_apps: 1,
+                user_runtimes: 0,
+            })
+        );
+        let flatpak = PackageCount::new("flatpak", count_flatpak(&roots).unwrap());
+        assert_eq!(flatpak.count, 4);
+    }
+
+    #[test]
+    fn test_flatpak_options() {
+        let (roots, _) = roots("packages-flatpak-list");
+        // Installations in other locations are only found by running flatpak
+        assert_eq!(read_flatpak_dirs(&roots), None);
+        let apps = "system\nuser\nextra\n";
+        let runtimes = "system\nsystem\nuser\n";
+        let flatpak = PackageCount::new("flatpak", parse_flatpak_list(apps, runtimes).into());
+        assert_eq!(flatpak.count, 6);
+
+        let mut config = config();
+        assert_eq!(
+            flatpak.entries(&config, &roots),
+            vec![(6, String::from("flatpak"))]
+        );
+        config.flatpak_apps_only = true;
+        assert_eq!(
+            flatpak.entries(&config, &roots),
+            vec![(3, String::from("flatpak"))]
+        );
+        config.split_flatpak = true;
+        assert_eq!(
+            flatpak.entries(&config, &roots),
+            vec![
+                (2, String::from("flatpak-system")),
+                (1, String::from("flatpak-user"))
+            ]
+        );
+        config.flatpak_apps_only = false;
+        assert_eq!(
+            flatpak.entries(&config, &roots),
+            vec![
+                (4, String::from("flatpak-system")),
+                (2, String::from("flatpak-user"))
+            ]
+        );
+
+        // Empty installations still show a count
+        let empty = PackageCount::new("flatpak", parse_flatpak_list("", "").into());
+        assert_eq!(
+            empty.entries(&config, &roots),
+            vec![(0, String::from("flatpak"))]
+        );
+    }
+
+    #[test]
+    fn test_parse_dpkg_status() {
+        let status = "Package: bash
+Essential: yes
+Status: install ok installed
+Architecture: amd64
+Description: GNU Bourne Again SHell
+ Bash is an sh-compatible command language interpreter.
+ Status: not a field
+
+Package: nano
+Status: deinstall ok config-files
+Architecture: amd64
+
+Package: tzdata
+Status: hold ok installed
+Architecture: all
+
+Package: libc6
+Status: install ok installed
+Architecture: amd64
+";
+        let installed = parse_dpkg_status(status);
+        assert_eq!(
+            installed,
+            vec![("bash", "amd64"), ("tzdata", "all"), ("libc6", "amd64")]
+        );
+
+        let extended_states = "Package: libc6
+Architecture: amd64
+Auto-Installed: 1
+
+Package: tzdata
+Architecture: amd64
+Auto-Installed: 1
+
+Package: nano
+Architecture: amd64
+Auto-Installed: 1
+
+Package: bash
+Architecture: amd64
+Auto-Installed: 0
+";
+        assert_eq!(count_automatic(&installed, extended_states), 2);
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs::{read_dir, read_link, read_to_string};
+use std::path::{Path, PathBuf};
+
+/// Contains a directory for each running process, named after its PID
+pub const PROC_PATH: &str = "/proc";
+
+/// Returns the directories of all processes in a directory like /proc
+pub fn process_dirs(proc_path: &Path) -> Vec<PathBuf> {
+    match read_dir(proc_path) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|dir| {
+                dir.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Returns the names of all running processes, read from /proc/<pid>/comm
+pub fn process_names(proc_path: &Path) -> Vec<String> {
+    process_dirs(proc_path)
+        .iter()
+        .filter_map(|dir| read_to_string(dir.join("comm")).ok())
+        .map(|name| name.trim().to_string())
+        .collect()
+}
+
+/// Parses the parent PID from the contents of /proc/<pid>/stat. The process
+/// name is in parentheses and may contain spaces, so the fields are counted
+/// from the last closing parenthesis.
+pub fn parse_parent_pid(stat: &str) -> Option<u32> {
+    stat[stat.rfind(')')? + 1..]
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+/// Returns the name of a process. comm is truncated to 15 characters, in
+/// which case the name of the executable is used instead.
+pub fn process_name(proc_path: &Path, pid: u32) -> Option<String> {
+    let dir = proc_path.join(pid.to_string());
+    let comm = read_to_string(dir.join("comm")).ok()?.trim().to_string();
+    if comm.len() < 15 {
+        return Some(comm);
+    }
+    let exe = read_link(dir.join("exe")).ok();
+    let exe_name = exe
+        .as_ref()
+        .and_then(|exe| exe.file_name()?.to_str())
+        .map(|name| name.trim_end_matches(" (deleted)").to_string());
+    Some(exe_name.unwrap_or(comm))
+}
+
+/// Returns the PIDs and names of the ancestors of a process, starting from
+/// its parent and ending at init
+pub fn ancestors(proc_path: &Path, pid: u32) -> Vec<(u32, String)> {
+    let mut ancestors = Vec::new();
+    let mut pid = pid;
+    // Guards against loops in malformed process trees
+    for _ in 0..64 {
+        let stat = match read_to_string(proc_path.join(pid.to_string()).join("stat")) {
+            Ok(s) => s,
+            Err(_) => break,
+        };
+        pid = match parse_parent_pid(&stat) {
+            Some(ppid) if ppid > 0 => ppid,
+            _ => break,
+        };
+        match process_name(proc_path, pid) {
+            Some(name) => ancestors.push((pid, name)),
+            None => break,
+        }
+    }
+    ancestors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    #[test]
+    fn test_ancestors() {
+        let dir = fixture_dir("proc-tree");
+        write_file(&dir, "1/stat", "1 (systemd) S 0 1 1 0 -1 4194560");
+        write_file(&dir, "1/comm", "systemd\n");
+        write_file(&dir, "520/stat", "520 (kitty) S 1 520 520 0 -1 4194304");
+        write_file(&dir, "520/comm", "kitty\n");
+        write_file(
+            &dir,
+            "600/stat",
+            "600 (tmux: client) S 520 600 600 34816 600",
+        );
+        write_file(&dir, "600/comm", "tmux: client\n");
+        write_file(
+            &dir,
+            "700/stat",
+            "700 (yan) R 600 700 600 34816 700 4194304",
+        );
+        write_file(&dir, "700/comm", "yan\n");
+
+        assert_eq!(parse_parent_pid("600 (a) b) S 520 600"), Some(520));
+        assert_eq!(
+            ancestors(&dir, 700),
+            vec![
+                (600, String::from("tmux: client")),
+                (520, String::from("kitty")),
+                (1, String::from("systemd"))
+            ]
+        );
+    }
+}
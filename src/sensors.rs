@@ -0,0 +1,314 @@
+use std::convert::TryFrom;
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+
+use ansi_term::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::Module;
+
+/// Each hwmon* directory is one chip, with a name file and files like
+/// temp1_input (millidegrees Celsius), temp1_label and fan1_input (RPM)
+const HWMON_PATH: &str = "/sys/class/hwmon";
+
+/// Unit used when outputting temperatures
+#[derive(Serialize, Deserialize)]
+enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    /// Converts a Celsius value and returns a formatted string like: "45.5°C"
+    fn to_unit_str(&self, celsius: f32) -> String {
+        match self {
+            Self::Celsius => format!("{:.1}°C", celsius),
+            Self::Fahrenheit => format!("{:.1}°F", celsius * 9.0 / 5.0 + 32.0),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Reading {
+    /// Degrees Celsius
+    Temperature(f32),
+    /// Rotations per minute
+    Fan(u32),
+}
+
+#[derive(Debug, PartialEq)]
+struct Sensor {
+    /// Name of the chip, like k10temp, coretemp or nvme
+    chip: String,
+    /// Name of the hwmon directory, like hwmon2, telling apart chips with the
+    /// same name
+    hwmon: String,
+    /// Label of the sensor, or the name of the input (like temp1) when the
+    /// chip doesn't provide labels
+    label: String,
+    reading: Reading,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sensors {
+    #[serde(skip)]
+    sensors: Vec<Sensor>,
+
+    header: String,
+    /// Sensors to show, either as "chip" to show all sensors of a chip or as
+    /// "chip/label" to show a single sensor, like "k10temp/Tctl"
+    selected: Vec<String>,
+    /// Unit used for temperatures. Possible values include Celsius and
+    /// Fahrenheit
+    unit: TemperatureUnit,
+    /// Temperature in Celsius from which the value is colored yellow
+    warning_threshold: f32,
+    /// Temperature in Celsius from which the value is colored red
+    critical_threshold: f32,
+}
+
+/// Reads all inputs of one kind (like temp or fan) of a hwmon chip
+fn read_inputs(dir: &Path, chip: &str, hwmon: &str, kind: &str) -> Vec<Sensor> {
+    let entries = match read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+    let mut inputs = entries
+        .filter_map(|entry| {
+            let file_name = entry.ok()?.file_name().into_string().ok()?;
+            let input = file_name.strip_suffix("_input")?;
+            input.strip_prefix(kind)?.parse::<u32>().ok()?;
+
+            let value = read_to_string(dir.join(&file_name)).ok()?;
+            let value = value.trim().parse::<i64>().ok()?;
+            let reading = match kind {
+                "temp" => Reading::Temperature(value as f32 / 1000.0),
+                _ => Reading::Fan(u32::try_from(value).ok()?),
+            };
+            let label = read_to_string(dir.join(format!("{}_label", input)))
+                .map(|l| l.trim().to_string())
+                .unwrap_or_else(|_| input.to_string());
+            Some(Sensor {
+                chip: chip.to_string(),
+                hwmon: hwmon.to_string(),
+                label,
+                reading,
+            })
+        })
+        .collect::<Vec<_>>();
+    inputs.sort_by(|a, b| a.label.cmp(&b.label));
+    inputs
+}
+
+/// Reads the sensors of all chips in a directory like /sys/class/hwmon
+fn get_sensors(path: &Path) -> Vec<Sensor> {
+    let entries = match read_dir(path) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+    let mut chips = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|dir| {
+            dir.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("hwmon"))
+        })
+        .collect::<Vec<_>>();
+    chips.sort();
+
+    chips
+        .iter()
+        .flat_map(|dir| {
+            let chip = read_to_string(dir.join("name"))
+                .map(|n| n.trim().to_string())
+                .unwrap_or_default();
+            let hwmon = dir.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            let mut sensors = read_inputs(dir, &chip, hwmon, "temp");
+            sensors.append(&mut read_inputs(dir, &chip, hwmon, "fan"));
+            sensors
+        })
+        .collect()
+}
+
+/// Returns the sensors matching the selection, in the order of the selection
+fn select<'a>(sensors: &'a [Sensor], selected: &[String]) -> Vec<&'a Sensor> {
+    selected
+        .iter()
+        .flat_map(|s| {
+            let (chip, label) = match s.split_once('/') {
+                Some((chip, label)) => (chip, Some(label)),
+                None => (s.as_str(), None),
+            };
+            sensors.iter().filter(move |sensor| {
+                sensor.chip == chip && label.is_none_or(|l| sensor.label == l)
+            })
+        })
+        .collect()
+}
+
+/// Returns the name to show for a selected sensor like: "nvme", "k10temp Tctl"
+/// or "nvme (hwmon2)" when several chips have the same name
+fn sensor_name(sensor: &Sensor, selected: &[&Sensor]) -> String {
+    let mut name = sensor.chip.clone();
+    if selected
+        .iter()
+        .any(|s| s.chip == sensor.chip && s.hwmon != sensor.hwmon)
+    {
+        name = format!("{} ({})", name, sensor.hwmon);
+    }
+    // Only add the label when several sensors of a chip are shown
+    if selected
+        .iter()
+        .filter(|s| s.chip == sensor.chip && s.hwmon == sensor.hwmon)
+        .count()
+        > 1
+    {
+        name = format!("{} {}", name, sensor.label);
+    }
+    name
+}
+
+impl Default for Sensors {
+    fn default() -> Self {
+        Self {
+            sensors: get_sensors(Path::new(HWMON_PATH)),
+            header: String::from("Sensors"),
+            selected: vec![
+                String::from("k10temp/Tctl"),
+                String::from("coretemp/Package id 0"),
+                String::from("nvme/Composite"),
+            ],
+            unit: TemperatureUnit::Celsius,
+            warning_threshold: 70.0,
+            critical_threshold: 90.0,
+        }
+    }
+}
+
+impl Module for Sensors {
+    fn print(&self, config: &Config) {
+        let selected = select(&self.sensors, &config.sensors.selected);
+        if selected.is_empty() {
+            return;
+        }
+
+        let readings = selected
+            .iter()
+            .map(|sensor| {
+                let name = sensor_name(sensor, &selected);
+                let value = match sensor.reading {
+                    Reading::Temperature(t) => {
+                        let value = config.sensors.unit.to_unit_str(t);
+                        if t >= config.sensors.critical_threshold {
+                            Color::Red.paint(value).to_string()
+                        } else if t >= config.sensors.warning_threshold {
+                            Color::Yellow.paint(value).to_string()
+                        } else {
+                            value
+                        }
+                    }
+                    Reading::Fan(rpm) => format!("{} RPM", rpm),
+                };
+                format!("{} {}", name, value)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        println!(
+            "{}: {}",
+            config.color.bold().paint(&config.sensors.header),
+            readings
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    #[test]
+    fn test_get_sensors() {
+        let dir = fixture_dir("hwmon");
+        write_file(&dir, "hwmon0/name", "k10temp\n");
+        write_file(&dir, "hwmon0/temp1_input", "45125\n");
+        write_file(&dir, "hwmon0/temp1_label", "Tctl\n");
+        write_file(&dir, "hwmon0/temp3_input", "38000\n");
+        write_file(&dir, "hwmon0/temp3_label", "Tccd1\n");
+        write_file(&dir, "hwmon1/name", "nvme\n");
+        write_file(&dir, "hwmon1/temp1_input", "35850\n");
+        write_file(&dir, "hwmon1/temp1_label", "Composite\n");
+        write_file(&dir, "hwmon1/temp1_max", "84850\n");
+        write_file(&dir, "hwmon2/name", "thinkpad\n");
+        write_file(&dir, "hwmon2/fan1_input", "2400\n");
+
+        let sensors = get_sensors(&dir);
+        assert_eq!(sensors.len(), 4);
+        assert_eq!(
+            sensors[0],
+            Sensor {
+                chip: String::from("k10temp"),
+                hwmon: String::from("hwmon0"),
+                label: String::from("Tccd1"),
+                reading: Reading::Temperature(38.0),
+            }
+        );
+        assert_eq!(
+            sensors[3],
+            Sensor {
+                chip: String::from("thinkpad"),
+                hwmon: String::from("hwmon2"),
+                label: String::from("fan1"),
+                reading: Reading::Fan(2400),
+            }
+        );
+
+        let selected = select(
+            &sensors,
+            &[String::from("nvme"), String::from("k10temp/Tctl")],
+        );
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected[0].label, "Composite");
+        assert_eq!(selected[1].reading, Reading::Temperature(45.125));
+        assert_eq!(sensor_name(selected[0], &selected), "nvme");
+    }
+
+    #[test]
+    fn test_sensor_name() {
+        let dir = fixture_dir("hwmon_duplicate");
+        write_file(&dir, "hwmon0/name", "nvme\n");
+        write_file(&dir, "hwmon0/temp1_input", "35850\n");
+        write_file(&dir, "hwmon0/temp1_label", "Composite\n");
+        write_file(&dir, "hwmon0/temp2_input", "40850\n");
+        write_file(&dir, "hwmon0/temp2_label", "Sensor 1\n");
+        write_file(&dir, "hwmon1/name", "nvme\n");
+        write_file(&dir, "hwmon1/temp1_input", "31850\n");
+        write_file(&dir, "hwmon1/temp1_label", "Composite\n");
+        // Negative values from faulty fans are skipped
+        write_file(&dir, "hwmon1/fan1_input", "-1\n");
+
+        let sensors = get_sensors(&dir);
+        assert_eq!(sensors.len(), 3);
+        let selected = select(&sensors, &[String::from("nvme")]);
+        let names = selected
+            .iter()
+            .map(|sensor| sensor_name(sensor, &selected))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "nvme (hwmon0) Composite",
+                "nvme (hwmon0) Sensor 1",
+                "nvme (hwmon1)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_temperature_unit() {
+        assert_eq!(TemperatureUnit::Celsius.to_unit_str(45.0), "45.0°C");
+        assert_eq!(TemperatureUnit::Fahrenheit.to_unit_str(45.0), "113.0°F");
+    }
+}
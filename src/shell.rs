@@ -1,20 +1,47 @@
 use serde::{Deserialize, Serialize};
 
 use std::env;
+use std::fs::read_link;
 use std::path::Path;
+use std::process::{self, Command};
 
 use crate::config::Config;
+use crate::process::{ancestors, PROC_PATH};
 use crate::Module;
 
+/// Process names of known shells
+pub const SHELLS: &[&str] = &[
+    "sh", "bash", "zsh", "fish", "dash", "ksh", "mksh", "oksh", "tcsh", "csh", "nu", "elvish",
+    "xonsh", "ion", "oil", "osh",
+];
+
+/// Variables some shells use for their version. These are usually not
+/// exported, but are used when they are.
+const VERSION_VARIABLES: &[(&str, &str)] = &[
+    ("bash", "BASH_VERSION"),
+    ("zsh", "ZSH_VERSION"),
+    ("fish", "FISH_VERSION"),
+    ("ksh", "KSH_VERSION"),
+];
+
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
 pub struct Shell {
+    /// Login shell from $SHELL
     #[serde(skip)]
     shell: Option<String>,
+    /// Path of the shell yan was started from
+    #[serde(skip)]
+    running_shell: Option<String>,
 
     header: String,
     /// Whether to show the full path of the shell
     show_path: bool,
+    /// Whether to show the shell yan was started from instead of the login
+    /// shell in $SHELL
+    show_running_shell: bool,
+    /// Whether to show the shell version
+    show_version: bool,
 }
 
 fn get_shell() -> Option<String> {
@@ -27,23 +54,77 @@ fn get_shell() -> Option<String> {
     }
 }
 
+/// Returns the path of the closest shell among the ancestors of yan
+fn get_running_shell() -> Option<String> {
+    let proc_path = Path::new(PROC_PATH);
+    let (pid, name) = ancestors(proc_path, process::id())
+        .into_iter()
+        .find(|(_, name)| SHELLS.contains(&name.as_str()))?;
+    match read_link(proc_path.join(pid.to_string()).join("exe")) {
+        Ok(path) => Some(path.to_string_lossy().to_string()),
+        Err(_) => Some(name),
+    }
+}
+
+/// Parses the version from the output of `shell --version`, like
+/// "GNU bash, version 5.2.15(1)-release (x86_64-pc-linux-gnu)"
+fn parse_version(output: &str) -> Option<String> {
+    let version = output
+        .lines()
+        .next()?
+        .split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()) && word.contains('.'))?
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next()?;
+    Some(version.to_string())
+}
+
+fn get_version(path: &str, name: &str) -> Option<String> {
+    let from_variable = VERSION_VARIABLES
+        .iter()
+        .find(|(shell, _)| *shell == name)
+        .and_then(|(_, variable)| env::var(variable).ok());
+    if let Some(version) = from_variable.as_deref().and_then(parse_version) {
+        return Some(version);
+    }
+
+    let output = Command::new(path).arg("--version").output().ok()?;
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
 impl Default for Shell {
     fn default() -> Self {
         Self {
             shell: get_shell(),
+            running_shell: get_running_shell(),
             header: String::from("Shell"),
             show_path: false,
+            show_running_shell: true,
+            show_version: false,
         }
     }
 }
 
 impl Module for Shell {
     fn print(&self, config: &Config) {
-        if let Some(shell) = &self.shell {
-            let mut shell = shell.clone();
-            if !config.shell.show_path {
-                let path = Path::new(&shell);
-                shell = String::from(path.file_name().unwrap().to_str().unwrap());
+        let shell = match (config.shell.show_running_shell, &self.running_shell) {
+            (true, Some(running_shell)) => Some(running_shell),
+            _ => self.shell.as_ref(),
+        };
+        if let Some(path) = shell {
+            let name = Path::new(path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(path);
+            let mut shell = if config.shell.show_path {
+                path.clone()
+            } else {
+                name.to_string()
+            };
+            if config.shell.show_version {
+                if let Some(version) = get_version(path, name) {
+                    shell.push_str(&format!(" {}", version));
+                }
             }
             println!(
                 "{}: {}",
@@ -53,3 +134,29 @@ impl Module for Shell {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            parse_version("GNU bash, version 5.2.15(1)-release (x86_64-pc-linux-gnu)\n"),
+            Some(String::from("5.2.15"))
+        );
+        assert_eq!(
+            parse_version("zsh 5.9 (x86_64-pc-linux-gnu)\n"),
+            Some(String::from("5.9"))
+        );
+        assert_eq!(
+            parse_version("fish, version 3.6.1\n"),
+            Some(String::from("3.6.1"))
+        );
+        assert_eq!(parse_version("0.88.1\n"), Some(String::from("0.88.1")));
+        assert_eq!(
+            parse_version("5.2.15(1)-release"),
+            Some(String::from("5.2.15"))
+        );
+        assert_eq!(parse_version(""), None);
+    }
+}
@@ -0,0 +1,298 @@
+use std::env;
+use std::path::Path;
+use std::process;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::process::{ancestors, PROC_PATH};
+use crate::shell::SHELLS;
+use crate::Module;
+
+/// Process names of known terminal emulators, with the name to show for them
+const TERMINALS: &[(&str, &str)] = &[
+    ("alacritty", "Alacritty"),
+    ("kitty", "kitty"),
+    ("foot", "foot"),
+    ("footclient", "foot"),
+    ("wezterm-gui", "WezTerm"),
+    ("ghostty", "Ghostty"),
+    ("gnome-terminal-server", "GNOME Terminal"),
+    ("kgx", "Console"),
+    ("konsole", "Konsole"),
+    ("yakuake", "Yakuake"),
+    ("xfce4-terminal", "Xfce Terminal"),
+    ("mate-terminal", "MATE Terminal"),
+    ("lxterminal", "LXTerminal"),
+    ("qterminal", "QTerminal"),
+    ("tilix", "Tilix"),
+    ("terminator", "Terminator"),
+    ("terminology", "Terminology"),
+    ("guake", "Guake"),
+    ("tilda", "Tilda"),
+    ("sakura", "Sakura"),
+    ("cool-retro-term", "cool-retro-term"),
+    ("deepin-terminal", "Deepin Terminal"),
+    ("contour", "Contour"),
+    ("rio", "Rio"),
+    ("xterm", "XTerm"),
+    ("urxvt", "URxvt"),
+    ("urxvtd", "URxvt"),
+    ("rxvt", "rxvt"),
+    ("st", "st"),
+    ("code", "VS Code"),
+];
+
+/// Processes other than shells between yan and the terminal
+const SKIPPED_PROCESSES: &[&str] = &[
+    "sudo",
+    "doas",
+    "su",
+    "login",
+    "script",
+    "ssh",
+    "sshd",
+    "mosh-server",
+    "tmux",
+    "screen",
+];
+
+/// Values of TERM_PROGRAM set by multiplexers rather than terminals
+const MULTIPLEXER_TERM_PROGRAMS: &[&str] = &["tmux", "screen"];
+
+/// Values of TERM that don't name a terminal: generic terminfo entries used
+/// by many terminals. Values starting with a multiplexer name, like
+/// tmux-256color, are skipped as well.
+const GENERIC_TERMS: &[&str] = &[
+    "xterm",
+    "xterm-color",
+    "xterm-16color",
+    "xterm-256color",
+    "xterm-direct",
+    "vt100",
+    "vt220",
+    "ansi",
+    "dumb",
+];
+
+/// Environment variables read by the module
+struct TerminalEnv {
+    term_program: Option<String>,
+    term: Option<String>,
+    tmux: bool,
+    screen: bool,
+    ssh: bool,
+}
+
+impl TerminalEnv {
+    fn load() -> Self {
+        Self {
+            term_program: env::var("TERM_PROGRAM").ok(),
+            term: env::var("TERM").ok(),
+            tmux: env::var_os("TMUX").is_some(),
+            screen: env::var_os("STY").is_some(),
+            ssh: env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct TerminalInfo {
+    name: Option<String>,
+    /// tmux or screen
+    multiplexer: Option<String>,
+    ssh: bool,
+}
+
+/// Returns whether a process is a multiplexer, like "tmux: client"
+fn is_multiplexer(process: &str, multiplexer: &str) -> bool {
+    process == multiplexer || process.starts_with(&format!("{}:", multiplexer))
+}
+
+fn detect(ancestors: &[String], env: &TerminalEnv) -> TerminalInfo {
+    let from_tree = ancestors
+        .iter()
+        .take_while(|p| {
+            SHELLS.contains(&p.as_str())
+                || SKIPPED_PROCESSES.contains(&p.as_str())
+                || TERMINALS.iter().any(|(process, _)| process == p)
+                || is_multiplexer(p, "tmux")
+        })
+        .find_map(|p| {
+            TERMINALS
+                .iter()
+                .find(|(process, _)| process == p)
+                .map(|(_, name)| name.to_string())
+        });
+    let from_env = env
+        .term_program
+        .as_ref()
+        .filter(|p| !MULTIPLEXER_TERM_PROGRAMS.contains(&p.as_str()))
+        .or_else(|| {
+            env.term.as_ref().filter(|t| {
+                !GENERIC_TERMS.contains(&t.as_str())
+                    && !MULTIPLEXER_TERM_PROGRAMS.iter().any(|m| t.starts_with(m))
+            })
+        })
+        .cloned();
+
+    let multiplexer = if env.tmux || ancestors.iter().any(|p| is_multiplexer(p, "tmux")) {
+        Some(String::from("tmux"))
+    } else if env.screen || ancestors.iter().any(|p| p == "screen") {
+        Some(String::from("screen"))
+    } else {
+        None
+    };
+
+    TerminalInfo {
+        name: from_tree.or(from_env),
+        multiplexer,
+        ssh: env.ssh || ancestors.iter().any(|p| p == "sshd"),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Terminal {
+    #[serde(skip)]
+    info: TerminalInfo,
+
+    header: String,
+    /// Whether to show if running inside tmux or screen
+    show_multiplexer: bool,
+    /// Whether to show if running over SSH
+    show_ssh: bool,
+}
+
+fn get_terminal_info() -> TerminalInfo {
+    let ancestors = ancestors(Path::new(PROC_PATH), process::id())
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect::<Vec<_>>();
+    detect(&ancestors, &TerminalEnv::load())
+}
+
+/// Returns the name of the terminal emulator yan is running in, like kitty or
+/// Alacritty
+pub fn get_terminal_name() -> Option<String> {
+    get_terminal_info().name
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self {
+            info: get_terminal_info(),
+            header: String::from("Terminal"),
+            show_multiplexer: true,
+            show_ssh: true,
+        }
+    }
+}
+
+impl Module for Terminal {
+    fn print(&self, config: &Config) {
+        let info = &self.info;
+        let mut details = Vec::new();
+        if let (true, Some(multiplexer)) = (config.terminal.show_multiplexer, &info.multiplexer) {
+            details.push(multiplexer.clone());
+        }
+        if config.terminal.show_ssh && info.ssh {
+            details.push(String::from("SSH"));
+        }
+
+        let mut terminal = match &info.name {
+            Some(name) => name.clone(),
+            None if details.is_empty() => return,
+            None => String::from("Unknown"),
+        };
+        if !details.is_empty() {
+            terminal.push_str(&format!(" ({})", details.join(", ")));
+        }
+        println!(
+            "{}: {}",
+            config.color.bold().paint(&config.terminal.header),
+            terminal
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn empty_env() -> TerminalEnv {
+        TerminalEnv {
+            term_program: None,
+            term: None,
+            tmux: false,
+            screen: false,
+            ssh: false,
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let ancestors = names(&["zsh", "sudo", "bash", "gnome-terminal-server", "systemd"]);
+        assert_eq!(
+            detect(&ancestors, &empty_env()),
+            TerminalInfo {
+                name: Some(String::from("GNOME Terminal")),
+                multiplexer: None,
+                ssh: false,
+            }
+        );
+
+        // Inside tmux the shell is a child of the tmux server, so the
+        // terminal can't be found in the process tree
+        let ancestors = names(&["bash", "tmux: server", "systemd"]);
+        let env = TerminalEnv {
+            term_program: Some(String::from("tmux")),
+            term: Some(String::from("tmux-256color")),
+            tmux: true,
+            ..empty_env()
+        };
+        assert_eq!(
+            detect(&ancestors, &env),
+            TerminalInfo {
+                name: None,
+                multiplexer: Some(String::from("tmux")),
+                ssh: false,
+            }
+        );
+
+        // TERM only names the terminal when it isn't a generic value
+        let env = TerminalEnv {
+            term: Some(String::from("xterm-256color")),
+            ..empty_env()
+        };
+        assert_eq!(detect(&ancestors, &env).name, None);
+        let env = TerminalEnv {
+            term: Some(String::from("foot")),
+            ..empty_env()
+        };
+        assert_eq!(detect(&ancestors, &env).name, Some(String::from("foot")));
+
+        // Over SSH the process tree ends at sshd
+        let ancestors = names(&["bash", "sshd", "sshd", "systemd"]);
+        let env = TerminalEnv {
+            term_program: Some(String::from("WezTerm")),
+            ..empty_env()
+        };
+        assert_eq!(
+            detect(&ancestors, &env),
+            TerminalInfo {
+                name: Some(String::from("WezTerm")),
+                multiplexer: None,
+                ssh: true,
+            }
+        );
+
+        // Unknown processes stop the search
+        let ancestors = names(&["bash", "cargo", "kitty"]);
+        assert_eq!(detect(&ancestors, &empty_env()).name, None);
+    }
+}
@@ -0,0 +1,382 @@
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::terminal::get_terminal_name;
+use crate::Module;
+
+#[derive(Debug, PartialEq)]
+struct Font {
+    family: String,
+    size: Option<String>,
+}
+
+impl Font {
+    fn new(family: &str, size: Option<&str>) -> Self {
+        Self {
+            family: family.to_string(),
+            size: size.map(String::from),
+        }
+    }
+
+    /// Fills in the family and size the terminal uses when they aren't
+    /// configured
+    fn with_defaults(family: Option<&str>, size: Option<&str>, default: Font) -> Self {
+        Self {
+            family: family.map_or(default.family, String::from),
+            size: size.map(String::from).or(default.size),
+        }
+    }
+}
+
+/// Parses a fontconfig pattern like "Fira Code:size=11", used by foot and
+/// Xresources. Only the first font of a list of fallbacks is used.
+fn parse_fontconfig_pattern(pattern: &str) -> Font {
+    let pattern = pattern.split(',').next().unwrap_or("");
+    let mut parts = pattern.split(':');
+    let family = parts.next().unwrap_or("").trim();
+    let size = parts.find_map(|part| part.trim().strip_prefix("size="));
+    Font::new(family, size)
+}
+
+/// Parses kitty.conf, where options are in the form:
+/// font_family      JetBrains Mono
+/// font_size        11.0
+fn parse_kitty(contents: &str) -> Font {
+    let option = |name: &str| {
+        contents.lines().rev().find_map(|line| {
+            let (key, value) = line.trim().split_once(char::is_whitespace)?;
+            if key != name {
+                return None;
+            }
+            // Newer versions also accept: font_family family="JetBrains Mono"
+            let value = value.trim();
+            let value = match value.strip_prefix("family=") {
+                Some(family) => family.trim_start_matches('"').split('"').next()?,
+                None => value,
+            };
+            Some(value)
+        })
+    };
+    Font::with_defaults(
+        option("font_family"),
+        option("font_size"),
+        Font::new("monospace", Some("11.0")),
+    )
+}
+
+fn alacritty_default() -> Font {
+    Font::new("monospace", Some("11.25"))
+}
+
+/// Parses the [font] table of alacritty.toml
+fn parse_alacritty_toml(contents: &str) -> Font {
+    let value = contents.parse::<toml::Value>().ok();
+    let font = value.as_ref().and_then(|v| v.get("font"));
+    let family = font
+        .and_then(|f| f.get("normal"))
+        .and_then(|n| n.get("family"))
+        .and_then(|f| f.as_str());
+    let size = font.and_then(|f| f.get("size")).map(|s| match s {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    });
+    Font::with_defaults(family, size.as_deref(), alacritty_default())
+}
+
+/// Parses the font of alacritty.yml, which is in the form:
+/// font:
+///   normal:
+///     family: Hack
+///   size: 11.0
+fn parse_alacritty_yml(contents: &str) -> Font {
+    let mut path: Vec<(usize, &str)> = Vec::new();
+    let mut family = None;
+    let mut size = None;
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        let (key, value) = match trimmed.split_once(':') {
+            Some(kv) => kv,
+            None => continue,
+        };
+        while path.last().is_some_and(|(i, _)| *i >= indent) {
+            path.pop();
+        }
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        let keys = path.iter().map(|(_, k)| *k).collect::<Vec<_>>();
+        match (keys.as_slice(), key.trim()) {
+            (["font", "normal"], "family") => family = Some(value),
+            (["font"], "size") => size = Some(value),
+            _ => (),
+        }
+        if value.is_empty() {
+            path.push((indent, key.trim()));
+        }
+    }
+    Font::with_defaults(family, size, alacritty_default())
+}
+
+/// Parses the font option in the [main] section of foot.ini, like:
+/// font=Fira Code:size=11
+fn parse_foot(contents: &str) -> Font {
+    let mut section = "main";
+    let mut font = None;
+    for line in contents.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[') {
+            section = name.trim_end_matches(']');
+        } else if let Some((key, value)) = line.split_once('=') {
+            if section == "main" && key.trim() == "font" {
+                font = Some(parse_fontconfig_pattern(value.trim()));
+            }
+        }
+    }
+    let default = Font::new("monospace", Some("8"));
+    match font {
+        Some(font) => Font::with_defaults(Some(&font.family), font.size.as_deref(), default),
+        None => default,
+    }
+}
+
+/// Parses the font from wezterm.lua, like:
+/// config.font = wezterm.font("JetBrains Mono")
+/// config.font_size = 12.0
+fn parse_wezterm(contents: &str) -> Font {
+    // Called like wezterm.font("X"), wezterm.font_with_fallback({ "X" }) or
+    // wezterm.font_with_fallback { "X" }
+    let family = contents
+        .match_indices("wezterm.font")
+        .find_map(|(index, call)| {
+            let args = contents[index + call.len()..].trim_start_matches("_with_fallback");
+            let args = args.trim_start().strip_prefix(|c| c == '(' || c == '{')?;
+            let args = args.trim_start().trim_start_matches('{').trim_start();
+            let quote = args.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            args[1..].split(quote).next()
+        });
+    let size = contents.lines().find_map(|line| {
+        let line = line.trim();
+        if line.starts_with("--") {
+            return None;
+        }
+        let value = line[line.find("font_size")? + "font_size".len()..].trim_start();
+        let value = value.strip_prefix('=')?.trim();
+        Some(value.trim_end_matches(',').trim())
+    });
+    Font::with_defaults(family, size, Font::new("JetBrains Mono", Some("12.0")))
+}
+
+/// Returns the value of a resource for a class from Xresources, like
+/// XTerm*faceName: Hack. Resources for the class take precedence over
+/// wildcards like *faceName.
+fn xresource<'a>(contents: &'a str, class: &str, resource: &str) -> Option<&'a str> {
+    let mut wildcard = None;
+    for line in contents.lines().map(str::trim) {
+        let (key, value) = match line.split_once(':') {
+            Some(kv) if !line.starts_with('!') => kv,
+            _ => continue,
+        };
+        let key = key.trim();
+        if !key.ends_with(resource) {
+            continue;
+        }
+        if key.to_lowercase().starts_with(&class.to_lowercase()) {
+            return Some(value.trim());
+        } else if key.starts_with('*') {
+            wildcard = Some(value.trim());
+        }
+    }
+    wildcard
+}
+
+/// Parses the font of XTerm or URxvt from Xresources
+fn parse_xresources(contents: &str, terminal: &str) -> Option<Font> {
+    match terminal {
+        "XTerm" => {
+            let family = xresource(contents, "xterm", "faceName")?;
+            let family = family.strip_prefix("xft:").unwrap_or(family);
+            let mut font = parse_fontconfig_pattern(family);
+            if let Some(size) = xresource(contents, "xterm", "faceSize") {
+                font.size = Some(size.to_string());
+            }
+            Some(font)
+        }
+        "URxvt" => {
+            let font = xresource(contents, "urxvt", "font")?;
+            Some(parse_fontconfig_pattern(
+                font.strip_prefix("xft:").unwrap_or(font),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the contents of the first config file that exists
+fn read_first(paths: &[Option<PathBuf>]) -> Option<String> {
+    paths
+        .iter()
+        .flatten()
+        .find_map(|path| read_to_string(path).ok())
+}
+
+fn get_font(terminal: &str) -> Option<Font> {
+    let config_file = |file: &str| dirs::config_dir().map(|dir| dir.join(file));
+    let home_file = |file: &str| dirs::home_dir().map(|dir| dir.join(file));
+    match terminal {
+        "kitty" => Some(parse_kitty(
+            &read_first(&[config_file("kitty/kitty.conf")]).unwrap_or_default(),
+        )),
+        "Alacritty" => match read_first(&[config_file("alacritty/alacritty.toml")]) {
+            Some(contents) => Some(parse_alacritty_toml(&contents)),
+            None => Some(parse_alacritty_yml(
+                &read_first(&[config_file("alacritty/alacritty.yml")]).unwrap_or_default(),
+            )),
+        },
+        "foot" => Some(parse_foot(
+            &read_first(&[config_file("foot/foot.ini")]).unwrap_or_default(),
+        )),
+        "WezTerm" => Some(parse_wezterm(
+            &read_first(&[
+                config_file("wezterm/wezterm.lua"),
+                home_file(".wezterm.lua"),
+            ])
+            .unwrap_or_default(),
+        )),
+        "XTerm" | "URxvt" => parse_xresources(
+            &read_first(&[home_file(".Xresources"), home_file(".Xdefaults")])?,
+            terminal,
+        ),
+        _ => None,
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalFont {
+    #[serde(skip)]
+    font: Option<Font>,
+
+    header: String,
+    /// Whether to add the font size
+    show_size: bool,
+}
+
+impl Default for TerminalFont {
+    fn default() -> Self {
+        Self {
+            font: get_terminal_name().and_then(|terminal| get_font(&terminal)),
+            header: String::from("Terminal Font"),
+            show_size: true,
+        }
+    }
+}
+
+impl Module for TerminalFont {
+    fn print(&self, config: &Config) {
+        if let Some(font) = &self.font {
+            let mut terminal_font = font.family.clone();
+            if let (true, Some(size)) = (config.terminal_font.show_size, &font.size) {
+                terminal_font.push_str(&format!(" {}", size));
+            }
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.terminal_font.header),
+                terminal_font
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_parse_kitty() {
+        let input = "# font_family Hack
+font_family      JetBrains Mono
+bold_font        auto
+font_size 11.5
+";
+        assert_eq!(
+            parse_kitty(input),
+            Font::new("JetBrains Mono", Some("11.5"))
+        );
+        assert_eq!(
+            parse_kitty("font_family family=\"Fira Code\" style=Retina\n"),
+            Font::new("Fira Code", Some("11.0"))
+        );
+    }
+
+    #[test]
+    fn test_parse_alacritty() {
+        let input = "[font]
+size = 10.5
+
+[font.normal]
+family = \"Hack\"
+style = \"Regular\"
+";
+        assert_eq!(parse_alacritty_toml(input), Font::new("Hack", Some("10.5")));
+
+        let input = "window:
+  opacity: 0.9
+font:
+  normal:
+    family: \"Iosevka\"
+    style: Regular
+  bold:
+    family: Hack
+  size: 12
+";
+        assert_eq!(parse_alacritty_yml(input), Font::new("Iosevka", Some("12")));
+        assert_eq!(parse_alacritty_yml(""), alacritty_default());
+    }
+
+    #[test]
+    fn test_parse_foot() {
+        let input = "[main]
+font=Fira Code:size=11,Noto Color Emoji:size=11
+dpi-aware=yes
+
+[csd]
+font=Cantarell
+";
+        assert_eq!(parse_foot(input), Font::new("Fira Code", Some("11")));
+        assert_eq!(parse_foot(""), Font::new("monospace", Some("8")));
+    }
+
+    #[test]
+    fn test_parse_wezterm() {
+        let input = "local wezterm = require 'wezterm'
+local config = wezterm.config_builder()
+config.font = wezterm.font_with_fallback { 'Victor Mono', 'Noto Color Emoji' }
+config.font_size = 13.0
+return config
+";
+        assert_eq!(parse_wezterm(input), Font::new("Victor Mono", Some("13.0")));
+        assert_eq!(parse_wezterm(""), Font::new("JetBrains Mono", Some("12.0")));
+    }
+
+    #[test]
+    fn test_parse_xresources() {
+        let input = "! XTerm*faceName: Ignored
+*faceName: DejaVu Sans Mono
+XTerm*faceName: xft:Hack
+xterm*faceSize: 10
+URxvt.font: xft:Source Code Pro:size=9,xft:Noto Emoji
+";
+        assert_eq!(
+            parse_xresources(input, "XTerm"),
+            Some(Font::new("Hack", Some("10")))
+        );
+        assert_eq!(
+            parse_xresources(input, "URxvt"),
+            Some(Font::new("Source Code Pro", Some("9")))
+        );
+        assert_eq!(parse_xresources(input, "kitty"), None);
+    }
+}
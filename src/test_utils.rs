@@ -0,0 +1,19 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Creates an empty directory in the system temp directory to build fixture
+/// trees in. Any previous contents of the directory are removed.
+pub fn fixture_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("yan-test-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed to create fixture directory");
+    dir
+}
+
+/// Writes a file inside a fixture directory, creating parent directories as
+/// needed
+pub fn write_file(dir: &Path, path: &str, contents: impl AsRef<[u8]>) {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap()).expect("Failed to create fixture directory");
+    fs::write(path, contents).expect("Failed to write fixture file");
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::Module;
+
+/// System wide GTK settings, used when the user has none
+const GTK3_SYSTEM_SETTINGS_PATH: &str = "/etc/gtk-3.0/settings.ini";
+const GTK4_SYSTEM_SETTINGS_PATH: &str = "/etc/gtk-4.0/settings.ini";
+
+/// Parses GTK settings into a HashMap. settings.ini is in the form:
+/// [Settings]
+/// gtk-theme-name=Adwaita
+/// and .gtkrc-2.0 is in the form:
+/// gtk-theme-name="Adwaita"
+fn parse(contents: &str) -> HashMap<&str, &str> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with(';'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, val)| (key.trim(), val.trim().trim_matches('"')))
+        .collect()
+}
+
+/// GTK settings of each GTK version, as (version, contents)
+struct GtkSettings {
+    files: Vec<(&'static str, String)>,
+}
+
+impl GtkSettings {
+    fn load() -> Self {
+        let config_dir = dirs::config_dir();
+        let user_file = |file: &str| config_dir.as_ref().map(|dir| dir.join(file));
+        let sources: Vec<(&str, Vec<Option<PathBuf>>)> = vec![
+            (
+                "GTK2",
+                vec![dirs::home_dir().map(|dir| dir.join(".gtkrc-2.0"))],
+            ),
+            (
+                "GTK3",
+                vec![
+                    user_file("gtk-3.0/settings.ini"),
+                    Some(PathBuf::from(GTK3_SYSTEM_SETTINGS_PATH)),
+                ],
+            ),
+            (
+                "GTK4",
+                vec![
+                    user_file("gtk-4.0/settings.ini"),
+                    Some(PathBuf::from(GTK4_SYSTEM_SETTINGS_PATH)),
+                ],
+            ),
+        ];
+
+        let files = sources
+            .into_iter()
+            .filter_map(|(version, paths)| {
+                let contents = paths
+                    .into_iter()
+                    .flatten()
+                    .find_map(|path| read_to_string(path).ok())?;
+                Some((version, contents))
+            })
+            .collect();
+        Self { files }
+    }
+
+    /// Returns the value of a setting for each GTK version that has it
+    fn get(&self, key: &str) -> Vec<(&'static str, String)> {
+        self.files
+            .iter()
+            .filter_map(|(version, contents)| {
+                let value = parse(contents).get(key)?.to_string();
+                Some((*version, value))
+            })
+            .collect()
+    }
+}
+
+/// Formats values of a setting like: "Adwaita-dark [GTK3/4], Adwaita [GTK2]",
+/// grouping GTK versions that use the same value
+fn format_values(values: &[(&str, String)]) -> Option<String> {
+    let mut groups: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (version, value) in values.iter() {
+        let version = version.trim_start_matches("GTK");
+        match groups.iter_mut().find(|(v, _)| v == value) {
+            Some((_, versions)) => versions.push(version),
+            None => groups.push((value, vec![version])),
+        }
+    }
+    if groups.is_empty() {
+        return None;
+    }
+    let formatted = groups
+        .iter()
+        .map(|(value, versions)| format!("{} [GTK{}]", value, versions.join("/")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(formatted)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct GtkTheme {
+    #[serde(skip)]
+    theme: Option<String>,
+
+    header: String,
+}
+
+impl Default for GtkTheme {
+    fn default() -> Self {
+        Self {
+            theme: format_values(&GtkSettings::load().get("gtk-theme-name")),
+            header: String::from("Theme"),
+        }
+    }
+}
+
+impl Module for GtkTheme {
+    fn print(&self, config: &Config) {
+        if let Some(theme) = &self.theme {
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.gtk_theme.header),
+                theme
+            );
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Icons {
+    #[serde(skip)]
+    icons: Option<String>,
+
+    header: String,
+}
+
+impl Default for Icons {
+    fn default() -> Self {
+        Self {
+            icons: format_values(&GtkSettings::load().get("gtk-icon-theme-name")),
+            header: String::from("Icons"),
+        }
+    }
+}
+
+impl Module for Icons {
+    fn print(&self, config: &Config) {
+        if let Some(icons) = &self.icons {
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.icons.header),
+                icons
+            );
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Cursor {
+    /// Format: (GTK version, cursor theme)
+    #[serde(skip)]
+    cursor: Vec<(&'static str, String)>,
+    #[serde(skip)]
+    size: Option<String>,
+
+    header: String,
+    /// Whether to add the cursor size
+    show_size: bool,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        let settings = GtkSettings::load();
+        Self {
+            cursor: settings.get("gtk-cursor-theme-name"),
+            size: settings
+                .get("gtk-cursor-theme-size")
+                .into_iter()
+                .map(|(_, size)| size)
+                .next(),
+            header: String::from("Cursor"),
+            show_size: false,
+        }
+    }
+}
+
+impl Module for Cursor {
+    fn print(&self, config: &Config) {
+        let mut cursor = self.cursor.clone();
+        if let (true, Some(size)) = (config.cursor.show_size, &self.size) {
+            for (_, name) in cursor.iter_mut() {
+                name.push_str(&format!(" ({}px)", size));
+            }
+        }
+        if let Some(cursor) = format_values(&cursor) {
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.cursor.header),
+                cursor
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_parse_gtk_settings() {
+        let input = "[Settings]
+gtk-theme-name=Adwaita-dark
+gtk-icon-theme-name = Papirus-Dark
+# gtk-cursor-theme-name=Adwaita
+gtk-font-name=Cantarell 11
+";
+        let settings = parse(input);
+        assert_eq!(settings.get("gtk-theme-name"), Some(&"Adwaita-dark"));
+        assert_eq!(settings.get("gtk-icon-theme-name"), Some(&"Papirus-Dark"));
+        assert_eq!(settings.get("gtk-cursor-theme-name"), None);
+
+        let input = "gtk-theme-name=\"Adwaita\"
+gtk-icon-theme-name=\"Papirus-Dark\"
+";
+        assert_eq!(parse(input).get("gtk-theme-name"), Some(&"Adwaita"));
+    }
+
+    #[test]
+    fn test_format_values() {
+        let values = vec![
+            ("GTK2", String::from("Adwaita")),
+            ("GTK3", String::from("Adwaita-dark")),
+            ("GTK4", String::from("Adwaita-dark")),
+        ];
+        assert_eq!(
+            format_values(&values),
+            Some(String::from("Adwaita [GTK2], Adwaita-dark [GTK3/4]"))
+        );
+        assert_eq!(format_values(&[]), None);
+    }
+}
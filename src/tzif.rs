@@ -0,0 +1,483 @@
+use std::env;
+use std::fs::read;
+use std::path::Path;
+
+use crate::calendar::{civil_from_days, days_from_civil, is_leap_year};
+
+/// Time zone data of the system time zone, usually a symlink into the
+/// database
+const LOCALTIME_PATH: &str = "etc/localtime";
+/// Time zone database with a TZif file per zone, like Europe/Berlin
+const ZONEINFO_PATH: &str = "usr/share/zoneinfo";
+
+/// Size of the TZif header, see RFC 8536
+const HEADER_SIZE: usize = 44;
+
+#[derive(Debug, Clone, PartialEq)]
+struct LocalTimeType {
+    /// Offset from UTC in seconds
+    offset: i64,
+    is_dst: bool,
+    abbreviation: String,
+}
+
+/// Day of the year a daylight saving time rule applies on
+#[derive(Debug, PartialEq)]
+enum RuleDay {
+    /// Jn: day 1 to 365, not counting February 29
+    Julian(i64),
+    /// n: day 0 to 365, counting February 29
+    ZeroBased(i64),
+    /// Mm.w.d: day d (0 for Sunday) of week w (5 for the last) of month m
+    MonthWeekDay(u32, u32, u32),
+}
+
+#[derive(Debug, PartialEq)]
+struct DstRule {
+    dst: LocalTimeType,
+    start: RuleDay,
+    /// Local standard time of the start of daylight saving time, in seconds
+    start_time: i64,
+    end: RuleDay,
+    /// Local daylight saving time of its end, in seconds
+    end_time: i64,
+}
+
+/// POSIX TZ string like CET-1CEST,M3.5.0,M10.5.0/3, which describes the
+/// rules after the last transition of a TZif file
+#[derive(Debug, PartialEq)]
+struct PosixTz {
+    std: LocalTimeType,
+    dst: Option<DstRule>,
+}
+
+/// Parses a zone abbreviation, either alphabetic like CET or quoted like
+/// <+0530>
+fn parse_abbreviation(s: &str) -> Option<(String, &str)> {
+    if let Some(rest) = s.strip_prefix('<') {
+        let end = rest.find('>')?;
+        return Some((rest[..end].to_string(), &rest[end + 1..]));
+    }
+    let end = s
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(s.len());
+    if end < 3 {
+        return None;
+    }
+    Some((s[..end].to_string(), &s[end..]))
+}
+
+/// Parses a time like -1, 5:30 or 25:00:00 into seconds
+fn parse_time(s: &str) -> Option<(i64, &str)> {
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let end = s
+        .find(|c: char| !c.is_ascii_digit() && c != ':')
+        .unwrap_or(s.len());
+    let mut secs = 0;
+    for (i, part) in s[..end].split(':').enumerate() {
+        let value: i64 = part.parse().ok()?;
+        secs += value * [3600, 60, 1].get(i)?;
+    }
+    Some((sign * secs, &s[end..]))
+}
+
+fn parse_rule_day(s: &str) -> Option<(RuleDay, &str)> {
+    let end = s.find([',', '/']).unwrap_or(s.len());
+    let (day, rest) = s.split_at(end);
+    let rule_day = if let Some(day) = day.strip_prefix('J') {
+        RuleDay::Julian(day.parse().ok()?)
+    } else if let Some(day) = day.strip_prefix('M') {
+        let mut parts = day.split('.').map(|p| p.parse::<u32>().ok());
+        RuleDay::MonthWeekDay(parts.next()??, parts.next()??, parts.next()??)
+    } else {
+        RuleDay::ZeroBased(day.parse().ok()?)
+    };
+    Some((rule_day, rest))
+}
+
+/// Parses a rule day with an optional time, which defaults to 02:00:00
+fn parse_rule(s: &str) -> Option<(RuleDay, i64, &str)> {
+    let (day, rest) = parse_rule_day(s)?;
+    match rest.strip_prefix('/') {
+        Some(time) => {
+            let (time, rest) = parse_time(time)?;
+            Some((day, time, rest))
+        }
+        None => Some((day, 7200, rest)),
+    }
+}
+
+fn parse_posix_tz(s: &str) -> Option<PosixTz> {
+    let (std_name, rest) = parse_abbreviation(s)?;
+    // POSIX offsets are positive west of Greenwich, unlike TZif offsets
+    let (std_offset, rest) = parse_time(rest)?;
+    let std = LocalTimeType {
+        offset: -std_offset,
+        is_dst: false,
+        abbreviation: std_name,
+    };
+    if rest.is_empty() {
+        return Some(PosixTz { std, dst: None });
+    }
+
+    let (dst_name, rest) = parse_abbreviation(rest)?;
+    let (dst_offset, rest) = match rest.is_empty() || rest.starts_with(',') {
+        true => (std.offset + 3600, rest),
+        false => {
+            let (offset, rest) = parse_time(rest)?;
+            (-offset, rest)
+        }
+    };
+    // Rules default to the ones of the United States
+    let rules = match rest.strip_prefix(',') {
+        Some(rules) => rules,
+        None if rest.is_empty() => "M3.2.0,M11.1.0",
+        None => return None,
+    };
+    let (start, start_time, rest) = parse_rule(rules)?;
+    let (end, end_time, _) = parse_rule(rest.strip_prefix(',')?)?;
+    Some(PosixTz {
+        std,
+        dst: Some(DstRule {
+            dst: LocalTimeType {
+                offset: dst_offset,
+                is_dst: true,
+                abbreviation: dst_name,
+            },
+            start,
+            start_time,
+            end,
+            end_time,
+        }),
+    })
+}
+
+/// Returns days since the Unix epoch of the day a rule applies on in a year
+fn rule_day(rule: &RuleDay, year: i64) -> i64 {
+    let jan1 = days_from_civil(year, 1, 1);
+    match *rule {
+        RuleDay::Julian(day) => {
+            let leap_day = if is_leap_year(year) && day > 59 { 1 } else { 0 };
+            jan1 + day - 1 + leap_day
+        }
+        RuleDay::ZeroBased(day) => jan1 + day,
+        RuleDay::MonthWeekDay(month, week, weekday) => {
+            let first = days_from_civil(year, month, 1);
+            let first_weekday = (first + 4).rem_euclid(7) as u32;
+            let mut day = first + ((weekday + 7 - first_weekday) % 7) as i64;
+            day += (week as i64 - 1) * 7;
+            // Week 5 means the last one, which may be the fourth
+            let next_month = match month {
+                12 => days_from_civil(year + 1, 1, 1),
+                _ => days_from_civil(year, month + 1, 1),
+            };
+            while day >= next_month {
+                day -= 7;
+            }
+            day
+        }
+    }
+}
+
+impl PosixTz {
+    fn local_time_type(&self, timestamp: i64) -> &LocalTimeType {
+        let rule = match &self.dst {
+            Some(rule) => rule,
+            None => return &self.std,
+        };
+        let (year, _, _) = civil_from_days((timestamp + self.std.offset).div_euclid(86400));
+        let start = rule_day(&rule.start, year) * 86400 + rule.start_time - self.std.offset;
+        let end = rule_day(&rule.end, year) * 86400 + rule.end_time - rule.dst.offset;
+        let in_dst = if start < end {
+            start <= timestamp && timestamp < end
+        } else {
+            // Southern hemisphere, where daylight saving time spans new year
+            !(end <= timestamp && timestamp < start)
+        };
+        match in_dst {
+            true => &rule.dst,
+            false => &self.std,
+        }
+    }
+}
+
+/// Time zone parsed from a TZif file or a POSIX TZ string
+#[derive(Debug, PartialEq)]
+pub struct TimeZone {
+    /// Transition times and the index of the local time type from then on
+    transitions: Vec<(i64, usize)>,
+    types: Vec<LocalTimeType>,
+    /// Rule for times after the last transition
+    rule: Option<PosixTz>,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+impl TimeZone {
+    /// Parses a TZif file, see RFC 8536. Version 1 files have 32 bit
+    /// transition times, later versions repeat the data with 64 bit times
+    /// followed by a POSIX TZ string footer.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if !data.starts_with(b"TZif") {
+            return None;
+        }
+        let version = *data.get(4)?;
+        let counts = |header: usize| -> Option<[usize; 6]> {
+            let mut counts = [0; 6];
+            for (i, count) in counts.iter_mut().enumerate() {
+                *count = read_u32(data, header + 20 + i * 4)? as usize;
+            }
+            Some(counts)
+        };
+        // isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt
+        let block_size = |c: [usize; 6], time_size: usize| {
+            c[3] * time_size + c[3] + c[4] * 6 + c[5] + c[2] * (time_size + 4) + c[1] + c[0]
+        };
+
+        let (header, time_size) = match version {
+            0 => (0, 4),
+            _ => (HEADER_SIZE + block_size(counts(0)?, 4), 8),
+        };
+        let [_, _, _, timecnt, typecnt, charcnt] = counts(header)?;
+        let data_start = header + HEADER_SIZE;
+        let data = data.get(data_start..)?;
+
+        let indices_start = timecnt * time_size;
+        let types_start = indices_start + timecnt;
+        let chars_start = types_start + typecnt * 6;
+        let chars = data.get(chars_start..chars_start + charcnt)?;
+
+        let mut transitions = Vec::new();
+        for i in 0..timecnt {
+            let bytes = data.get(i * time_size..(i + 1) * time_size)?;
+            let time = match time_size {
+                4 => i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as i64,
+                _ => {
+                    let mut time = [0; 8];
+                    time.copy_from_slice(bytes);
+                    i64::from_be_bytes(time)
+                }
+            };
+            transitions.push((time, *data.get(indices_start + i)? as usize));
+        }
+
+        let mut types = Vec::new();
+        for i in 0..typecnt {
+            let ttinfo = data.get(types_start + i * 6..types_start + (i + 1) * 6)?;
+            let offset = i32::from_be_bytes([ttinfo[0], ttinfo[1], ttinfo[2], ttinfo[3]]);
+            let chars = chars.get(ttinfo[5] as usize..)?;
+            let end = chars.iter().position(|c| *c == 0).unwrap_or(chars.len());
+            types.push(LocalTimeType {
+                offset: offset as i64,
+                is_dst: ttinfo[4] != 0,
+                abbreviation: String::from_utf8_lossy(&chars[..end]).to_string(),
+            });
+        }
+        if types.is_empty() || transitions.iter().any(|(_, i)| *i >= types.len()) {
+            return None;
+        }
+
+        // The footer is a TZ string between newlines, after the data
+        let rule = match version {
+            0 => None,
+            _ => {
+                let footer = data.get(block_size(counts(header)?, 8)..)?;
+                let footer = String::from_utf8_lossy(footer);
+                parse_posix_tz(footer.trim_matches('\n'))
+            }
+        };
+        Some(Self {
+            transitions,
+            types,
+            rule,
+        })
+    }
+
+    /// Parses a POSIX TZ string like EST5EDT or <+0530>-5:30
+    pub fn from_posix(tz: &str) -> Option<Self> {
+        Some(Self {
+            transitions: Vec::new(),
+            types: Vec::new(),
+            rule: Some(parse_posix_tz(tz)?),
+        })
+    }
+
+    /// Returns the offset from UTC in seconds and the zone abbreviation at a
+    /// point in time
+    pub fn offset_at(&self, timestamp: i64) -> (i64, String) {
+        let local_time_type = match self.transitions.last() {
+            Some((last, _)) if timestamp < *last => {
+                match self
+                    .transitions
+                    .iter()
+                    .rposition(|(time, _)| *time <= timestamp)
+                {
+                    Some(i) => &self.types[self.transitions[i].1],
+                    // Before the first transition, the first standard time
+                    // type applies
+                    None => self
+                        .types
+                        .iter()
+                        .find(|t| !t.is_dst)
+                        .unwrap_or(&self.types[0]),
+                }
+            }
+            last => match (&self.rule, last) {
+                (Some(rule), _) => rule.local_time_type(timestamp),
+                (None, Some((_, index))) => &self.types[*index],
+                (None, None) => &self.types[0],
+            },
+        };
+        (local_time_type.offset, local_time_type.abbreviation.clone())
+    }
+}
+
+/// Loads a time zone. The name can be a zone in the database like
+/// Europe/Berlin, a path to a TZif file or a POSIX TZ string, with an
+/// optional leading colon like in TZ. Without a name, the system time zone is
+/// loaded. Paths are relative to root, so that tests can use a fixture tree.
+pub fn load(root: &Path, name: Option<&str>) -> Option<TimeZone> {
+    let name = match name.map(|n| n.strip_prefix(':').unwrap_or(n)) {
+        Some(name) if !name.is_empty() => name,
+        _ => return TimeZone::parse(&read(root.join(LOCALTIME_PATH)).ok()?),
+    };
+    let path = match name.strip_prefix('/') {
+        Some(path) => root.join(path),
+        None => root.join(ZONEINFO_PATH).join(name),
+    };
+    // Names like ../../etc/shadow must not escape the database
+    let file = match name.contains("..") {
+        true => None,
+        false => read(path).ok().and_then(|data| TimeZone::parse(&data)),
+    };
+    file.or_else(|| TimeZone::from_posix(name))
+}
+
+/// Returns the offset from UTC in seconds and the zone abbreviation at a
+/// point in time, like load for the time zone. Falls back to UTC when the time
+/// zone can't be loaded.
+pub fn zone_offset(root: &Path, name: Option<&str>, timestamp: i64) -> (i64, String) {
+    match load(root, name) {
+        Some(zone) => zone.offset_at(timestamp),
+        None => {
+            if let Some(name) = name {
+                eprintln!("Failed to load time zone {}", name);
+            }
+            (0, String::from("UTC"))
+        }
+    }
+}
+
+/// Returns the offset from UTC and the zone abbreviation of the local time
+/// zone, from TZ or /etc/localtime
+pub fn local_offset(timestamp: i64) -> (i64, String) {
+    zone_offset(Path::new("/"), env::var("TZ").ok().as_deref(), timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    /// Builds a version 2 TZif file with an empty version 1 block
+    fn build_tzif(
+        transitions: &[(i64, u8)],
+        types: &[(i32, bool, u8)],
+        chars: &[u8],
+        footer: &str,
+    ) -> Vec<u8> {
+        let header = |timecnt: usize, typecnt: usize, charcnt: usize| {
+            let mut header = b"TZif2".to_vec();
+            header.resize(20, 0);
+            for count in [0, 0, 0, timecnt, typecnt, charcnt] {
+                header.extend_from_slice(&(count as u32).to_be_bytes());
+            }
+            header
+        };
+        let mut data = header(0, 1, 1);
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0]);
+
+        data.append(&mut header(transitions.len(), types.len(), chars.len()));
+        for (time, _) in transitions {
+            data.extend_from_slice(&time.to_be_bytes());
+        }
+        for (_, index) in transitions {
+            data.push(*index);
+        }
+        for (offset, is_dst, index) in types {
+            data.extend_from_slice(&offset.to_be_bytes());
+            data.push(*is_dst as u8);
+            data.push(*index);
+        }
+        data.extend_from_slice(chars);
+        data.extend_from_slice(format!("\n{}\n", footer).as_bytes());
+        data
+    }
+
+    /// Europe/Berlin with transitions up to 2023, and a rule after that
+    fn berlin() -> Vec<u8> {
+        build_tzif(
+            // 2023-03-26 01:00 and 2023-10-29 01:00 UTC
+            &[(1679792400, 1), (1698541200, 0)],
+            &[(3600, false, 0), (7200, true, 4)],
+            b"CET\0CEST\0",
+            "CET-1CEST,M3.5.0,M10.5.0/3",
+        )
+    }
+
+    #[test]
+    fn test_parse_tzif() {
+        let zone = TimeZone::parse(&berlin()).unwrap();
+        // From the transitions
+        assert_eq!(zone.offset_at(1679792399), (3600, String::from("CET")));
+        assert_eq!(zone.offset_at(1679792400), (7200, String::from("CEST")));
+        assert_eq!(zone.offset_at(0), (3600, String::from("CET")));
+        // From the footer rule, around the start of 2024's summer time on
+        // 2024-03-31 01:00 UTC and its end on 2024-10-27 01:00 UTC
+        assert_eq!(zone.offset_at(1711846799), (3600, String::from("CET")));
+        assert_eq!(zone.offset_at(1711846800), (7200, String::from("CEST")));
+        assert_eq!(zone.offset_at(1729990799), (7200, String::from("CEST")));
+        assert_eq!(zone.offset_at(1729990800), (3600, String::from("CET")));
+
+        assert_eq!(TimeZone::parse(b"not a time zone"), None);
+    }
+
+    #[test]
+    fn test_posix_tz() {
+        // Southern hemisphere, summer time from October to April
+        let sydney = TimeZone::from_posix("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        // 2030-01-15 and 2030-07-01
+        assert_eq!(sydney.offset_at(1894665600), (39600, String::from("AEDT")));
+        assert_eq!(sydney.offset_at(1908748800), (36000, String::from("AEST")));
+
+        let kolkata = TimeZone::from_posix("<+0530>-5:30").unwrap();
+        assert_eq!(kolkata.offset_at(0), (19800, String::from("+0530")));
+        let new_york = TimeZone::from_posix("EST5EDT").unwrap();
+        assert_eq!(
+            new_york.offset_at(1908748800),
+            (-14400, String::from("EDT"))
+        );
+        assert_eq!(TimeZone::from_posix("X"), None);
+    }
+
+    #[test]
+    fn test_load() {
+        let root = fixture_dir("tzif");
+        write_file(&root, LOCALTIME_PATH, berlin());
+        write_file(&root, "usr/share/zoneinfo/Europe/Berlin", berlin());
+        let berlin = TimeZone::parse(&berlin());
+        assert_eq!(load(&root, None), berlin);
+        assert_eq!(load(&root, Some(":Europe/Berlin")), berlin);
+        assert_eq!(
+            load(&root, Some("UTC0")).map(|zone| zone.offset_at(0)),
+            Some((0, String::from("UTC")))
+        );
+        assert_eq!(load(&root, Some("../../etc/localtime")), None);
+    }
+}
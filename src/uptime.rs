@@ -1,7 +1,12 @@
-use std::fs::read_to_string;
+use std::env;
+use std::fs::{read_to_string, OpenOptions};
+use std::io::{self, Read};
+use std::mem;
+use std::os::unix::fs::OpenOptionsExt;
 
 use serde::{Deserialize, Serialize};
 
+use crate::calendar::CalendarTime;
 use crate::Config;
 use crate::Module;
 
@@ -9,6 +14,21 @@ use crate::Module;
 /// system has been on (in seconds) and the second value is the sum of how much
 /// time each core has spent idle.
 const UPTIME_PATH: &str = "/proc/uptime";
+/// Contains the boot time as seconds since the Unix epoch on the btime line
+const STAT_PATH: &str = "/proc/stat";
+/// Kernel log, with one record per read like:
+/// 6,1234,5678901234,-;PM: suspend exit
+const KMSG_PATH: &str = "/dev/kmsg";
+/// Kernel log messages written when the system wakes up
+const RESUME_MESSAGES: &[&str] = &["PM: suspend exit", "PM: hibernation exit"];
+
+/// Largest unit durations are broken down into
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum LargestUnit {
+    Days,
+    Weeks,
+    Years,
+}
 
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
@@ -19,11 +39,140 @@ pub struct Uptime {
     /// Uptime in seconds
     uptime: u32,
 
+    #[serde(skip)]
+    /// Boot time in seconds since the Unix epoch
+    boot_time: Option<i64>,
+
     /// When true, uptime will be outputted like: 2d 10h 3m
     short_output: bool,
+    /// Largest unit of the uptime, Days, Weeks or Years
+    largest_unit: LargestUnit,
+    /// Whether to translate unit names to the language of the locale
+    localized_units: bool,
+    /// Whether to add the time since the system last resumed from suspend or
+    /// hibernation, which is read from the kernel log
+    show_since_resume: bool,
+
+    boot_time_header: String,
+    /// Whether to show the boot time on a separate line
+    show_boot_time: bool,
+    /// strftime-like pattern of the boot time, see calendar.rs for the
+    /// supported conversions
+    boot_time_format: String,
+}
+
+/// Singular and plural names of the units of a duration
+pub struct UnitNames {
+    year: (&'static str, &'static str),
+    week: (&'static str, &'static str),
+    day: (&'static str, &'static str),
+    hour: (&'static str, &'static str),
+    min: (&'static str, &'static str),
+    sec: (&'static str, &'static str),
 }
 
-struct Duration {
+const ENGLISH: UnitNames = UnitNames {
+    year: ("year", "years"),
+    week: ("week", "weeks"),
+    day: ("day", "days"),
+    hour: ("hour", "hours"),
+    min: ("min", "mins"),
+    sec: ("sec", "secs"),
+};
+
+/// Unit names by language code
+const UNIT_NAMES: &[(&str, UnitNames)] = &[
+    (
+        "de",
+        UnitNames {
+            year: ("Jahr", "Jahre"),
+            week: ("Woche", "Wochen"),
+            day: ("Tag", "Tage"),
+            hour: ("Stunde", "Stunden"),
+            min: ("Minute", "Minuten"),
+            sec: ("Sekunde", "Sekunden"),
+        },
+    ),
+    (
+        "es",
+        UnitNames {
+            year: ("año", "años"),
+            week: ("semana", "semanas"),
+            day: ("día", "días"),
+            hour: ("hora", "horas"),
+            min: ("minuto", "minutos"),
+            sec: ("segundo", "segundos"),
+        },
+    ),
+    (
+        "fr",
+        UnitNames {
+            year: ("an", "ans"),
+            week: ("semaine", "semaines"),
+            day: ("jour", "jours"),
+            hour: ("heure", "heures"),
+            min: ("minute", "minutes"),
+            sec: ("seconde", "secondes"),
+        },
+    ),
+    (
+        "it",
+        UnitNames {
+            year: ("anno", "anni"),
+            week: ("settimana", "settimane"),
+            day: ("giorno", "giorni"),
+            hour: ("ora", "ore"),
+            min: ("minuto", "minuti"),
+            sec: ("secondo", "secondi"),
+        },
+    ),
+    (
+        "nl",
+        UnitNames {
+            year: ("jaar", "jaar"),
+            week: ("week", "weken"),
+            day: ("dag", "dagen"),
+            hour: ("uur", "uur"),
+            min: ("minuut", "minuten"),
+            sec: ("seconde", "seconden"),
+        },
+    ),
+    (
+        "pt",
+        UnitNames {
+            year: ("ano", "anos"),
+            week: ("semana", "semanas"),
+            day: ("dia", "dias"),
+            hour: ("hora", "horas"),
+            min: ("minuto", "minutos"),
+            sec: ("segundo", "segundos"),
+        },
+    ),
+];
+
+/// Returns the unit names for a locale like de_DE.UTF-8, falling back to
+/// English
+fn unit_names(locale: Option<&str>) -> &'static UnitNames {
+    let language = locale
+        .and_then(|l| l.split(['_', '.', '@']).next())
+        .unwrap_or("");
+    UNIT_NAMES
+        .iter()
+        .find(|(code, _)| *code == language)
+        .map_or(&ENGLISH, |(_, names)| names)
+}
+
+/// Returns the locale used for messages, which the unit names belong to
+fn messages_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+}
+
+pub struct Duration {
+    years: u32,
+    weeks: u32,
     days: u32,
     hours: u32,
     mins: u32,
@@ -31,52 +180,69 @@ struct Duration {
 }
 
 impl Duration {
-    fn from_secs(secs: u32) -> Self {
+    pub fn from_secs(secs: u32) -> Self {
+        Self::from_secs_up_to(secs, LargestUnit::Days)
+    }
+
+    /// Breaks down the seconds into units up to the largest one. A year is
+    /// counted as 365 days.
+    pub fn from_secs_up_to(secs: u32, largest_unit: LargestUnit) -> Self {
+        let mut days = secs / 60 / 60 / 24;
+        let years = match largest_unit {
+            LargestUnit::Years => days / 365,
+            _ => 0,
+        };
+        days -= years * 365;
+        let weeks = match largest_unit {
+            LargestUnit::Days => 0,
+            _ => days / 7,
+        };
+        days -= weeks * 7;
         Self {
-            days: secs / 60 / 60 / 24,
+            years,
+            weeks,
+            days,
             hours: secs / 60 / 60 % 24,
             mins: secs / 60 % 60,
             secs,
         }
     }
 
-    fn to_string(&self, short: bool) -> String {
-        let mut result = String::new();
-
-        match self.days {
-            0 => (),
-            1 => result.push_str("1 day, "),
-            _ => result.push_str(&format!("{} days, ", self.days)),
-        };
-        match self.hours {
-            0 => (),
-            1 => result.push_str("1 hour, "),
-            _ => result.push_str(&format!("{} hours, ", self.hours)),
-        };
-        match self.mins {
-            0 => (),
-            1 => result.push_str("1 min"),
-            _ => result.push_str(&format!("{} mins, ", self.mins)),
-        };
+    pub fn to_string(&self, short: bool) -> String {
+        self.to_string_with(short, &ENGLISH)
+    }
 
-        if result.is_empty() {
-            result.push_str(&format!("{} secs", self.secs));
+    /// Formats the duration like "2 days, 7 hours, 33 mins", or "2d 7h 33m"
+    /// when short. Seconds are only shown for durations under a minute.
+    pub fn to_string_with(&self, short: bool, names: &UnitNames) -> String {
+        let units = [
+            (self.years, names.year, 'y'),
+            (self.weeks, names.week, 'w'),
+            (self.days, names.day, 'd'),
+            (self.hours, names.hour, 'h'),
+            (self.mins, names.min, 'm'),
+        ];
+        let mut parts = units
+            .iter()
+            .filter(|(value, _, _)| *value > 0)
+            .collect::<Vec<_>>();
+        let secs = (self.secs, names.sec, 's');
+        if parts.is_empty() {
+            parts.push(&secs);
         }
 
-        if short {
-            result = result
-                .replace(" days,", "d")
-                .replace(" day,", "d")
-                .replace(" hours,", "h")
-                .replace(" hour,", "h")
-                .replace(" mins", "m")
-                .replace(" min", "m")
-                .replace(" secs", "s");
-        }
-        // In case duration is exactly some amount of days or hours
-        result
-            .trim_end_matches(|c: char| c == ',' || c.is_whitespace())
-            .to_string()
+        let parts = parts.iter().map(|(value, (singular, plural), letter)| {
+            if short {
+                format!("{}{}", value, letter)
+            } else if *value == 1 {
+                format!("{} {}", value, singular)
+            } else {
+                format!("{} {}", value, plural)
+            }
+        });
+        parts
+            .collect::<Vec<_>>()
+            .join(if short { " " } else { ", " })
     }
 }
 
@@ -91,24 +257,121 @@ fn get_uptime() -> u32 {
         .expect("Failed to parse uptime") as u32
 }
 
+fn parse_boot_time(stat: &str) -> Option<i64> {
+    stat.lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Returns the timestamp in microseconds of the last resume from the records
+/// of the kernel log
+fn parse_last_resume(records: &[String]) -> Option<u64> {
+    records.iter().rev().find_map(|record| {
+        let (prefix, message) = record.split_once(';')?;
+        if !RESUME_MESSAGES.iter().any(|m| message.starts_with(m)) {
+            return None;
+        }
+        prefix.split(',').nth(2)?.parse().ok()
+    })
+}
+
+/// Reads all records of the kernel log without blocking for new ones
+fn read_kernel_log() -> io::Result<Vec<String>> {
+    let mut kmsg = OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(KMSG_PATH)?;
+    let mut records = Vec::new();
+    let mut buf = vec![0u8; 8192];
+    loop {
+        match kmsg.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => records.push(String::from_utf8_lossy(&buf[..len]).to_string()),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+            // Records that were overwritten while reading are skipped
+            Err(e) if e.raw_os_error() == Some(libc::EPIPE) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(records)
+}
+
+/// Returns the seconds since the system last resumed from suspend or
+/// hibernation. Kernel log timestamps don't advance while suspended, like the
+/// monotonic clock they are compared with.
+fn get_time_since_resume() -> Option<u32> {
+    let records = match read_kernel_log() {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to read {}, {}", KMSG_PATH, e);
+            return None;
+        }
+    };
+    let resume = parse_last_resume(&records)?;
+
+    // SAFETY: now is only read after clock_gettime filled it in
+    let now = unsafe {
+        let mut now: libc::timespec = mem::zeroed();
+        if libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) != 0 {
+            return None;
+        }
+        now.tv_sec as u64 * 1_000_000 + now.tv_nsec as u64 / 1000
+    };
+    Some((now.saturating_sub(resume) / 1_000_000) as u32)
+}
+
 impl Default for Uptime {
     fn default() -> Self {
         Self {
             header: String::from("Uptime"),
             uptime: get_uptime(),
+            boot_time: read_to_string(STAT_PATH)
+                .ok()
+                .and_then(|stat| parse_boot_time(&stat)),
             short_output: false,
+            largest_unit: LargestUnit::Days,
+            localized_units: false,
+            show_since_resume: false,
+            boot_time_header: String::from("Boot Time"),
+            show_boot_time: false,
+            boot_time_format: String::from("%Y-%m-%d %H:%M"),
         }
     }
 }
 
 impl Module for Uptime {
     fn print(&self, config: &Config) {
-        let uptime = Duration::from_secs(self.uptime).to_string(config.uptime.short_output);
+        let names = if config.uptime.localized_units {
+            unit_names(messages_locale().as_deref())
+        } else {
+            &ENGLISH
+        };
+        let duration = |secs| {
+            Duration::from_secs_up_to(secs, config.uptime.largest_unit)
+                .to_string_with(config.uptime.short_output, names)
+        };
+
+        let mut uptime = duration(self.uptime);
+        if config.uptime.show_since_resume {
+            if let Some(secs) = get_time_since_resume() {
+                uptime.push_str(&format!(" (resumed {} ago)", duration(secs)));
+            }
+        }
         println!(
             "{}: {}",
             config.color.bold().paint(&config.uptime.header),
             uptime
         );
+
+        if let (true, Some(boot_time)) = (config.uptime.show_boot_time, self.boot_time) {
+            println!(
+                "{}: {}",
+                config.color.bold().paint(&config.uptime.boot_time_header),
+                CalendarTime::local(boot_time).format(&config.uptime.boot_time_format)
+            );
+        }
     }
 }
 
@@ -132,4 +395,43 @@ mod tests {
         let duration = Duration::from_secs(888888);
         assert_eq!(duration.to_string(true), String::from("10d 6h 54m"));
     }
+
+    #[test]
+    fn test_duration_units() {
+        // 400 days, 1 hour and 1 minute
+        let secs = 400 * 86400 + 3660;
+        assert_eq!(
+            Duration::from_secs_up_to(secs, LargestUnit::Weeks).to_string(true),
+            String::from("57w 1d 1h 1m")
+        );
+        assert_eq!(
+            Duration::from_secs_up_to(secs, LargestUnit::Years).to_string(false),
+            String::from("1 year, 5 weeks, 1 hour, 1 min")
+        );
+        assert_eq!(
+            Duration::from_secs(secs).to_string_with(false, unit_names(Some("de_DE.UTF-8"))),
+            String::from("400 Tage, 1 Stunde, 1 Minute")
+        );
+        assert_eq!(unit_names(Some("C.UTF-8")).day.1, "days");
+    }
+
+    #[test]
+    fn test_parse_kernel_records() {
+        assert_eq!(
+            parse_boot_time("cpu  10 0 20 300\nbtime 1697000000\nprocesses 1234\n"),
+            Some(1697000000)
+        );
+        let records = [
+            "6,1200,3000000000,-;PM: suspend entry (deep)\n",
+            "6,1230,3000500000,-;PM: suspend exit\n",
+            "6,1300,5000000000,-;PM: suspend entry (s2idle)\n",
+            "6,1330,5000100000,-;PM: suspend exit\n",
+            "4,1400,5000200000,-;usb 1-1: reset high-speed USB device\n",
+        ]
+        .iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>();
+        assert_eq!(parse_last_resume(&records), Some(5000100000));
+        assert_eq!(parse_last_resume(&records[..1]), None);
+    }
 }
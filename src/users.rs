@@ -0,0 +1,189 @@
+use std::fs::read;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::Module;
+
+/// Binary file with a record for each login session. Older systems use
+/// /var/run/utmp, newer ones /run/utmp with /var/run linking to /run.
+const UTMP_PATHS: &[&str] = &["/var/run/utmp", "/run/utmp"];
+
+/// Size of a utmp record on Linux with glibc
+const RECORD_SIZE: usize = 384;
+/// Offsets and sizes of the fields used from a record
+const TYPE_OFFSET: usize = 0;
+const LINE_OFFSET: usize = 8;
+const LINE_SIZE: usize = 32;
+const USER_OFFSET: usize = 44;
+const USER_SIZE: usize = 32;
+const HOST_OFFSET: usize = 76;
+const HOST_SIZE: usize = 256;
+/// Record type of a user login session
+const USER_PROCESS: i16 = 7;
+
+#[derive(Debug, PartialEq)]
+struct Session {
+    user: String,
+    /// Terminal of the session, like tty1 or pts/0
+    tty: String,
+    /// Host the user is logged in from, for remote sessions
+    host: Option<String>,
+}
+
+/// Reads a null padded string field from a record
+fn read_field(record: &[u8], offset: usize, size: usize) -> String {
+    let field = &record[offset..offset + size];
+    let end = field.iter().position(|b| *b == 0).unwrap_or(size);
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+/// Parses the user login sessions from the contents of utmp
+fn parse(contents: &[u8]) -> Vec<Session> {
+    contents
+        .chunks_exact(RECORD_SIZE)
+        .filter(|record| {
+            i16::from_ne_bytes([record[TYPE_OFFSET], record[TYPE_OFFSET + 1]]) == USER_PROCESS
+        })
+        .map(|record| {
+            let host = read_field(record, HOST_OFFSET, HOST_SIZE);
+            Session {
+                user: read_field(record, USER_OFFSET, USER_SIZE),
+                tty: read_field(record, LINE_OFFSET, LINE_SIZE),
+                host: if host.is_empty() { None } else { Some(host) },
+            }
+        })
+        .filter(|session| !session.user.is_empty())
+        .collect()
+}
+
+fn get_sessions() -> Vec<Session> {
+    UTMP_PATHS
+        .iter()
+        .find_map(|path| read(path).ok())
+        .map(|contents| parse(&contents))
+        .unwrap_or_default()
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Users {
+    #[serde(skip)]
+    sessions: Vec<Session>,
+
+    header: String,
+    /// Whether to show the terminals of each user
+    show_ttys: bool,
+    /// Whether to show the hosts of remote sessions
+    show_hosts: bool,
+    /// Whether to show the number of sessions
+    show_session_count: bool,
+}
+
+impl Default for Users {
+    fn default() -> Self {
+        Self {
+            sessions: get_sessions(),
+            header: String::from("Users"),
+            show_ttys: true,
+            show_hosts: true,
+            show_session_count: false,
+        }
+    }
+}
+
+impl Users {
+    /// Formats the sessions grouped by user like:
+    /// alice (tty1, pts/0 from 10.0.0.5), bob (pts/2)
+    fn format_sessions(&self, config: &Users) -> String {
+        let mut users: Vec<(&str, Vec<String>)> = Vec::new();
+        for session in self.sessions.iter() {
+            let mut tty = session.tty.clone();
+            if let (true, Some(host)) = (config.show_hosts, &session.host) {
+                tty.push_str(&format!(" from {}", host));
+            }
+            match users.iter_mut().find(|(user, _)| *user == session.user) {
+                Some((_, ttys)) => ttys.push(tty),
+                None => users.push((&session.user, vec![tty])),
+            }
+        }
+
+        users
+            .iter()
+            .map(|(user, ttys)| {
+                if config.show_ttys {
+                    format!("{} ({})", user, ttys.join(", "))
+                } else {
+                    user.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl Module for Users {
+    fn print(&self, config: &Config) {
+        if self.sessions.is_empty() {
+            return;
+        }
+        let mut users = self.format_sessions(&config.users);
+        if config.users.show_session_count {
+            let count = self.sessions.len();
+            let sessions = if count == 1 { "session" } else { "sessions" };
+            users = format!("{} {}: {}", count, sessions, users);
+        }
+        println!(
+            "{}: {}",
+            config.color.bold().paint(&config.users.header),
+            users
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(record_type: i16, user: &str, tty: &str, host: &str) -> Vec<u8> {
+        let mut record = vec![0u8; RECORD_SIZE];
+        record[TYPE_OFFSET..TYPE_OFFSET + 2].copy_from_slice(&record_type.to_ne_bytes());
+        record[LINE_OFFSET..LINE_OFFSET + tty.len()].copy_from_slice(tty.as_bytes());
+        record[USER_OFFSET..USER_OFFSET + user.len()].copy_from_slice(user.as_bytes());
+        record[HOST_OFFSET..HOST_OFFSET + host.len()].copy_from_slice(host.as_bytes());
+        record
+    }
+
+    #[test]
+    fn test_parse_utmp() {
+        // Boot time and run level records come before the sessions
+        let mut contents = record(2, "reboot", "~", "6.1.0-13-amd64");
+        contents.append(&mut record(1, "runlevel", "~", "6.1.0-13-amd64"));
+        contents.append(&mut record(6, "LOGIN", "tty2", ""));
+        contents.append(&mut record(USER_PROCESS, "alice", "tty1", ""));
+        contents.append(&mut record(USER_PROCESS, "bob", "pts/0", "10.0.0.5"));
+        contents.append(&mut record(USER_PROCESS, "alice", "pts/1", ":0"));
+        // Logged out session
+        contents.append(&mut record(8, "", "pts/2", ""));
+
+        let sessions = parse(&contents);
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(
+            sessions[1],
+            Session {
+                user: String::from("bob"),
+                tty: String::from("pts/0"),
+                host: Some(String::from("10.0.0.5")),
+            }
+        );
+
+        let users = Users {
+            sessions,
+            ..Users::default()
+        };
+        assert_eq!(
+            users.format_sessions(&users),
+            "alice (tty1, pts/1 from :0), bob (pts/0 from 10.0.0.5)"
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::fs::read_to_string;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::Module;
+
+/// Paths of the files used for detection, relative to the root directory
+const SYS_VENDOR_PATH: &str = "sys/class/dmi/id/sys_vendor";
+const PRODUCT_NAME_PATH: &str = "sys/class/dmi/id/product_name";
+const CPU_INFO_PATH: &str = "proc/cpuinfo";
+/// Only exists in Xen guests, containing "xen"
+const HYPERVISOR_TYPE_PATH: &str = "sys/hypervisor/type";
+const DOCKER_ENV_PATH: &str = ".dockerenv";
+const PODMAN_ENV_PATH: &str = "run/.containerenv";
+/// Environment of PID 1, where container managers set the container variable
+const INIT_ENVIRON_PATH: &str = "proc/1/environ";
+/// Written by systemd when it runs in a container, with the same value as the
+/// container variable
+const SYSTEMD_CONTAINER_PATH: &str = "run/systemd/container";
+const PROC_VERSION_PATH: &str = "proc/version";
+
+/// Hypervisors identified by substrings of the DMI vendor or product name
+const HYPERVISORS: &[(&str, &str)] = &[
+    ("KVM", "KVM"),
+    ("QEMU", "QEMU"),
+    ("VMware", "VMware"),
+    ("innotek GmbH", "VirtualBox"),
+    ("VirtualBox", "VirtualBox"),
+    ("Virtual Machine", "Hyper-V"),
+    ("Xen", "Xen"),
+    ("Parallels", "Parallels"),
+    ("Bochs", "Bochs"),
+    ("Amazon EC2", "Amazon EC2"),
+    ("Google Compute Engine", "Google Compute Engine"),
+];
+
+/// Values of the container variable and the names of their container managers
+const CONTAINERS: &[(&str, &str)] = &[
+    ("docker", "Docker"),
+    ("podman", "Podman"),
+    ("lxc", "LXC"),
+    ("lxc-libvirt", "LXC"),
+    ("systemd-nspawn", "systemd-nspawn"),
+    ("oci", "OCI"),
+    ("wsl", "WSL"),
+];
+
+#[derive(Debug, PartialEq)]
+struct Detected {
+    vm: Option<String>,
+    container: Option<String>,
+}
+
+fn read(root: &Path, path: &str) -> Option<String> {
+    read_to_string(root.join(path)).ok()
+}
+
+fn detect_vm(root: &Path) -> Option<String> {
+    let dmi = [SYS_VENDOR_PATH, PRODUCT_NAME_PATH]
+        .iter()
+        .filter_map(|path| read(root, path))
+        .collect::<Vec<_>>();
+    let from_dmi = HYPERVISORS.iter().find_map(|(id, name)| {
+        dmi.iter()
+            .any(|value| value.contains(id))
+            .then(|| name.to_string())
+    });
+    if from_dmi.is_some() {
+        return from_dmi;
+    }
+
+    if read(root, HYPERVISOR_TYPE_PATH).is_some_and(|t| t.trim() == "xen") {
+        return Some(String::from("Xen"));
+    }
+
+    // Set by the CPU in any VM, for hypervisors that aren't identified above
+    let cpu_info = read(root, CPU_INFO_PATH).unwrap_or_default();
+    let hypervisor_flag = cpu_info
+        .lines()
+        .filter(|line| line.starts_with("flags"))
+        .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"));
+    if hypervisor_flag {
+        return Some(String::from("VM"));
+    }
+    None
+}
+
+fn container_name(value: &str) -> String {
+    CONTAINERS
+        .iter()
+        .find(|(id, _)| *id == value)
+        .map_or(value, |(_, name)| name)
+        .to_string()
+}
+
+fn detect_container(root: &Path) -> Option<String> {
+    if root.join(DOCKER_ENV_PATH).exists() {
+        return Some(String::from("Docker"));
+    }
+    if root.join(PODMAN_ENV_PATH).exists() {
+        return Some(String::from("Podman"));
+    }
+
+    // Reading the environment of PID 1 requires root, so the file written by
+    // systemd is checked as well
+    let from_environ = read(root, INIT_ENVIRON_PATH).and_then(|environ| {
+        environ
+            .split('\0')
+            .find_map(|var| var.strip_prefix("container="))
+            .map(container_name)
+    });
+    let container = from_environ.or_else(|| {
+        read(root, SYSTEMD_CONTAINER_PATH)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .map(|value| container_name(&value))
+    });
+    if container.is_some() {
+        return container;
+    }
+
+    // The WSL kernel is built by Microsoft, like:
+    // Linux version 5.15.133.1-microsoft-standard-WSL2 ...
+    let version = read(root, PROC_VERSION_PATH).unwrap_or_default();
+    if version.to_lowercase().contains("microsoft") {
+        return Some(String::from("WSL"));
+    }
+    None
+}
+
+fn detect(root: &Path) -> Detected {
+    Detected {
+        vm: detect_vm(root),
+        container: detect_container(root),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Virtualization {
+    #[serde(skip)]
+    detected: Detected,
+
+    header: String,
+    /// Whether to show "Bare metal" when running outside a VM or container
+    show_bare_metal: bool,
+}
+
+impl Default for Virtualization {
+    fn default() -> Self {
+        Self {
+            detected: detect(Path::new("/")),
+            header: String::from("Virtualization"),
+            show_bare_metal: true,
+        }
+    }
+}
+
+impl Virtualization {
+    fn format_detected(&self) -> Option<String> {
+        match (&self.detected.container, &self.detected.vm) {
+            (Some(container), Some(vm)) => Some(format!("{} on {}", container, vm)),
+            (Some(container), None) => Some(container.clone()),
+            (None, Some(vm)) => Some(vm.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+impl Module for Virtualization {
+    fn print(&self, config: &Config) {
+        let output = match self.format_detected() {
+            Some(output) => output,
+            None if config.virtualization.show_bare_metal => String::from("Bare metal"),
+            None => return,
+        };
+        println!(
+            "{}: {}",
+            config.color.bold().paint(&config.virtualization.header),
+            output
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_dir, write_file};
+
+    #[test]
+    fn test_detect() {
+        let dir = fixture_dir("virtualization-bare-metal");
+        write_file(&dir, SYS_VENDOR_PATH, "LENOVO\n");
+        write_file(&dir, PRODUCT_NAME_PATH, "20XW0055GE\n");
+        write_file(&dir, CPU_INFO_PATH, "flags\t\t: fpu vme de pse\n");
+        write_file(&dir, PROC_VERSION_PATH, "Linux version 6.1.0-13-amd64\n");
+        assert_eq!(
+            detect(&dir),
+            Detected {
+                vm: None,
+                container: None
+            }
+        );
+
+        let dir = fixture_dir("virtualization-docker");
+        write_file(&dir, SYS_VENDOR_PATH, "QEMU\n");
+        write_file(&dir, PRODUCT_NAME_PATH, "Standard PC (Q35 + ICH9, 2009)\n");
+        write_file(&dir, DOCKER_ENV_PATH, "");
+        let virtualization = Virtualization {
+            detected: detect(&dir),
+            ..Virtualization::default()
+        };
+        assert_eq!(
+            virtualization.format_detected(),
+            Some(String::from("Docker on QEMU"))
+        );
+
+        let dir = fixture_dir("virtualization-lxc");
+        write_file(&dir, CPU_INFO_PATH, "flags\t\t: fpu vme hypervisor\n");
+        write_file(&dir, INIT_ENVIRON_PATH, "PATH=/sbin:/bin\0container=lxc\0");
+        assert_eq!(
+            detect(&dir),
+            Detected {
+                vm: Some(String::from("VM")),
+                container: Some(String::from("LXC"))
+            }
+        );
+
+        let dir = fixture_dir("virtualization-wsl");
+        write_file(
+            &dir,
+            PROC_VERSION_PATH,
+            "Linux version 5.15.133.1-microsoft-standard-WSL2 (root@1c602f52c2e4)\n",
+        );
+        assert_eq!(detect_container(&dir), Some(String::from("WSL")));
+    }
+}